@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use crate::discovery::{Discovery, DiscoveryError};
+
+// 检查文件变化的默认间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+type ServiceMap = HashMap<String, Vec<String>>;
+
+#[derive(Default)]
+struct Services {
+    current: ServiceMap,
+    senders: HashMap<String, watch::Sender<Vec<String>>>,
+}
+
+impl Services {
+    // 只通知端点列表确实变化了的订阅者，文件中删除的服务推送空列表
+    fn apply(&mut self, services: ServiceMap) {
+        for (name, sender) in &self.senders {
+            let endpoints = services.get(name).cloned().unwrap_or_default();
+            sender.send_if_modified(|current| {
+                if *current == endpoints {
+                    return false;
+                }
+                *current = endpoints;
+                true
+            });
+        }
+        self.current = services;
+    }
+}
+
+// 监视本地 TOML 或 JSON 文件，内容为服务名到地址列表的映射：
+//   echo = ["127.0.0.1:7000", "127.0.0.1:7001"]
+// 扩展名为 .json 时按 JSON 解析，其余按 TOML
+// 通过定期比较文件内容发现修改，编辑器先写临时文件再改名的方式也能识别
+pub struct FileDiscovery {
+    services: Arc<Mutex<Services>>,
+    handle: JoinHandle<()>,
+}
+
+impl FileDiscovery {
+    // 与 with_interval 相同，须在 tokio 运行时中调用
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, DiscoveryError> {
+        Self::with_interval(path, POLL_INTERVAL)
+    }
+
+    // 文件首次读取失败时直接返回错误，之后的读取或解析失败保留上一次的结果
+    // 轮询任务通过 tokio::spawn 启动，须在 tokio 运行时中调用，否则会 panic
+    pub fn with_interval(path: impl Into<PathBuf>, interval: Duration) -> Result<Self, DiscoveryError> {
+        let path = path.into();
+        let contents = std::fs::read_to_string(&path).map_err(DiscoveryError::Io)?;
+        let mut services = Services::default();
+        services.apply(parse(&path, &contents)?);
+        let services = Arc::new(Mutex::new(services));
+        let handle = tokio::spawn(poll(path, contents, interval, Arc::clone(&services)));
+        Ok(FileDiscovery { services, handle })
+    }
+
+    // 当前文件中的全部服务
+    pub fn services(&self) -> HashMap<String, Vec<String>> {
+        self.services.lock().unwrap().current.clone()
+    }
+}
+
+impl Discovery for FileDiscovery {
+    fn subscribe(&self, service: &str) -> watch::Receiver<Vec<String>> {
+        let mut services = self.services.lock().unwrap();
+        let endpoints = services.current.get(service).cloned().unwrap_or_default();
+        services
+            .senders
+            .entry(service.to_string())
+            .or_insert_with(|| watch::channel(endpoints).0)
+            .subscribe()
+    }
+}
+
+impl Drop for FileDiscovery {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn poll(path: PathBuf, mut last: String, interval: Duration, services: Arc<Mutex<Services>>) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let contents = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(e) => {
+                log::warn!("Failed to read discovery file {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        if contents == last {
+            continue;
+        }
+        match parse(&path, &contents) {
+            Ok(parsed) => {
+                log::info!("Reloaded discovery file {}", path.display());
+                services.lock().unwrap().apply(parsed);
+            }
+            // 写到一半的文件可能暂时无法解析，等下次变化再试
+            Err(e) => log::warn!("Ignoring invalid discovery file {}: {:?}", path.display(), e),
+        }
+        last = contents;
+    }
+}
+
+fn parse(path: &Path, contents: &str) -> Result<ServiceMap, DiscoveryError> {
+    let is_json = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    if is_json {
+        serde_json::from_str(contents).map_err(|e| DiscoveryError::Parse(e.to_string()))
+    } else {
+        toml::from_str(contents).map_err(|e| DiscoveryError::Parse(e.to_string()))
+    }
+}
@@ -0,0 +1,20 @@
+mod file;
+
+pub use file::FileDiscovery;
+
+use tokio::sync::watch;
+
+// 服务发现的来源，如本地文件或 DNS SRV 查询
+// 每个服务对应一个 watch 通道，端点列表变化时推送新的完整列表
+pub trait Discovery: Send + Sync {
+    // 未知服务返回空列表，之后出现时再推送
+    fn subscribe(&self, service: &str) -> watch::Receiver<Vec<String>>;
+}
+
+// 服务发现错误类型
+#[derive(Debug)]
+pub enum DiscoveryError {
+    Io(std::io::Error),
+    // 文件内容不是合法的服务表
+    Parse(String),
+}
@@ -1,4 +1,6 @@
 pub mod logger;
 pub mod protocol;
 pub mod transport;
-pub mod encrypt;
\ No newline at end of file
+pub mod encrypt;
+pub mod rpc;
+pub mod discovery;
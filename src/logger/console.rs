@@ -0,0 +1,232 @@
+use log::{Level, LevelFilter};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use super::queue::LogQueue;
+use super::{LogCommand, LogFormat, LogRecord};
+
+// 控制台输出配置
+#[derive(Clone, Debug)]
+pub struct ConsoleConfig {
+    pub enabled: bool,
+    // 缓冲达到该条数时批量打印
+    pub batch_size: usize,
+    // 缓冲中最早的一条等待超过该时间时打印
+    pub flush_interval: Duration,
+    // 该级别及更严重的日志不经缓冲立即打印
+    pub immediate_level: LevelFilter,
+    // 该级别及更严重的日志打印到 stderr，其余打印到 stdout
+    pub stderr_level: LevelFilter,
+}
+
+impl Default for ConsoleConfig {
+    fn default() -> Self {
+        ConsoleConfig {
+            enabled: true,
+            batch_size: 10,
+            flush_interval: Duration::from_millis(200),
+            immediate_level: LevelFilter::Error,
+            stderr_level: LevelFilter::Trace,
+        }
+    }
+}
+
+// stdout 被 StdioTransport 用来传输帧后置位，此后控制台日志一律输出到 stderr
+static STDOUT_RESERVED: AtomicBool = AtomicBool::new(false);
+
+// 声明 stdout 已被占用，日志器初始化前后调用均可
+pub(crate) fn reserve_stdout() {
+    STDOUT_RESERVED.store(true, Ordering::Relaxed);
+}
+
+pub(super) fn stdout_reserved() -> bool {
+    STDOUT_RESERVED.load(Ordering::Relaxed)
+}
+
+// 控制台的输出流，测试中替换为内存缓冲
+trait ConsoleOutput {
+    // 按原有顺序打印并清空缓冲，元素为 (是否输出到 stderr, 已格式化的日志)
+    fn print(&mut self, buffer: &mut Vec<(bool, String)>);
+}
+
+struct StdStreams;
+
+impl ConsoleOutput for StdStreams {
+    // stdout 与 stderr 各加锁一次
+    fn print(&mut self, buffer: &mut Vec<(bool, String)>) {
+        let mut stdout = io::stdout().lock();
+        let mut stderr = io::stderr().lock();
+        let reserved = stdout_reserved();
+        for (to_stderr, msg) in buffer.drain(..) {
+            let _ = if to_stderr || reserved {
+                stderr.write_all(msg.as_bytes())
+            } else {
+                stdout.write_all(msg.as_bytes())
+            };
+        }
+        let _ = stdout.flush();
+    }
+}
+
+// 后台控制台打印线程，批量处理
+pub(super) fn run_console_writer(config: ConsoleConfig, queue: &LogQueue) {
+    write_console(config, queue, &mut StdStreams);
+}
+
+fn write_console(config: ConsoleConfig, queue: &LogQueue, output: &mut impl ConsoleOutput) {
+    let _closing = queue.close_on_drop();
+    // (是否输出到 stderr, 已格式化的日志)
+    let mut buffer: Vec<(bool, String)> = Vec::with_capacity(config.batch_size);
+    let mut oldest: Option<Instant> = None;
+    loop {
+        // 有缓冲时最多等到刷新时间点，否则阻塞接收
+        let command = match oldest {
+            Some(oldest) => {
+                let timeout = (oldest + config.flush_interval).saturating_duration_since(Instant::now());
+                queue.pop_timeout(timeout)
+            }
+            None => Some(queue.pop()),
+        };
+        // 队列恢复后补记溢出期间丢弃的条数
+        let dropped = queue.take_dropped();
+        if dropped > 0 {
+            let record = LogRecord::internal(Level::Warn, format!("{} log messages dropped: queue full", dropped));
+            buffer.push((record.level <= config.stderr_level, LogFormat::Text.format(&record)));
+        }
+        match command {
+            Some(LogCommand::Record(record)) => {
+                let to_stderr = record.level <= config.stderr_level;
+                let msg = LogFormat::Text.format(&record);
+                buffer.push((to_stderr, msg));
+                oldest.get_or_insert_with(Instant::now);
+                if record.level <= config.immediate_level || buffer.len() >= config.batch_size {
+                    output.print(&mut buffer);
+                    oldest = None;
+                }
+            }
+            Some(LogCommand::Flush(ack)) => {
+                output.print(&mut buffer);
+                oldest = None;
+                let _ = ack.send(());
+            }
+            Some(LogCommand::Reopen(..) | LogCommand::SetFormat(_)) => {}
+            None => {
+                output.print(&mut buffer);
+                oldest = None;
+            }
+            Some(LogCommand::Shutdown) => {
+                // 收到停止命令，打印剩余
+                output.print(&mut buffer);
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::QueueConfig;
+    use std::sync::{Arc, Mutex};
+    use std::thread::{self, JoinHandle};
+
+    // 记录打印到 stdout 与 stderr 的内容
+    #[derive(Clone, Default)]
+    struct MemoryOutput {
+        stdout: Arc<Mutex<Vec<String>>>,
+        stderr: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ConsoleOutput for MemoryOutput {
+        fn print(&mut self, buffer: &mut Vec<(bool, String)>) {
+            for (to_stderr, msg) in buffer.drain(..) {
+                let stream = if to_stderr { &self.stderr } else { &self.stdout };
+                stream.lock().unwrap().push(msg);
+            }
+        }
+    }
+
+    impl MemoryOutput {
+        fn stdout(&self) -> Vec<String> {
+            self.stdout.lock().unwrap().clone()
+        }
+
+        fn stderr(&self) -> Vec<String> {
+            self.stderr.lock().unwrap().clone()
+        }
+    }
+
+    fn start(config: ConsoleConfig) -> (Arc<LogQueue>, MemoryOutput, JoinHandle<()>) {
+        let queue = Arc::new(LogQueue::new(QueueConfig::default()));
+        let output = MemoryOutput::default();
+        let (worker_queue, mut worker_output) = (Arc::clone(&queue), output.clone());
+        let handle = thread::spawn(move || write_console(config, &worker_queue, &mut worker_output));
+        (queue, output, handle)
+    }
+
+    fn log(queue: &LogQueue, level: Level, message: &str) {
+        queue.push(LogCommand::Record(Arc::new(LogRecord::internal(level, message.to_string()))));
+    }
+
+    fn stop(queue: &LogQueue, handle: JoinHandle<()>) {
+        queue.push(LogCommand::Shutdown);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn flushes_after_interval() {
+        let (queue, output, handle) = start(ConsoleConfig {
+            batch_size: 100,
+            flush_interval: Duration::from_millis(100),
+            stderr_level: LevelFilter::Off,
+            ..ConsoleConfig::default()
+        });
+        log(&queue, Level::Info, "buffered");
+        thread::sleep(Duration::from_millis(20));
+        assert!(output.stdout().is_empty());
+        thread::sleep(Duration::from_millis(300));
+        let lines = output.stdout();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("buffered"));
+        stop(&queue, handle);
+    }
+
+    #[test]
+    fn prints_immediate_level_at_once() {
+        let (queue, output, handle) = start(ConsoleConfig {
+            batch_size: 100,
+            flush_interval: Duration::from_secs(60),
+            immediate_level: LevelFilter::Warn,
+            stderr_level: LevelFilter::Off,
+            ..ConsoleConfig::default()
+        });
+        log(&queue, Level::Info, "first");
+        log(&queue, Level::Warn, "urgent");
+        thread::sleep(Duration::from_millis(100));
+        // 立即打印时连同之前缓冲的日志按顺序输出
+        let lines = output.stdout();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("first") && lines[1].contains("urgent"));
+        stop(&queue, handle);
+    }
+
+    #[test]
+    fn routes_by_level() {
+        let (queue, output, handle) = start(ConsoleConfig {
+            stderr_level: LevelFilter::Warn,
+            ..ConsoleConfig::default()
+        });
+        log(&queue, Level::Debug, "debug");
+        log(&queue, Level::Info, "info");
+        log(&queue, Level::Warn, "warn");
+        log(&queue, Level::Error, "error");
+        stop(&queue, handle);
+
+        let stdout = output.stdout();
+        let stderr = output.stderr();
+        assert_eq!(stdout.len(), 2);
+        assert!(stdout[0].contains("debug") && stdout[1].contains("info"));
+        assert_eq!(stderr.len(), 2);
+        assert!(stderr[0].contains("warn") && stderr[1].contains("error"));
+    }
+}
@@ -0,0 +1,75 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use uuid::Uuid;
+
+tokio::task_local! {
+    static LOG_CONTEXT: LogContext;
+}
+
+// 作用域内每条日志都会附带的上下文，文本格式作为前缀，JSON 格式作为字段
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogContext {
+    pub uuid: Option<Uuid>,
+    pub session_id: Option<u64>,
+    pub request_id: Option<u64>,
+    pub peer_addr: Option<SocketAddr>,
+}
+
+impl LogContext {
+    pub fn for_connection(uuid: Uuid, peer_addr: Option<SocketAddr>) -> Self {
+        LogContext {
+            uuid: Some(uuid),
+            peer_addr,
+            ..LogContext::default()
+        }
+    }
+
+    // 当前任务的上下文，不在任何作用域内时返回 None
+    pub fn current() -> Option<LogContext> {
+        LOG_CONTEXT.try_with(Clone::clone).ok()
+    }
+
+    // 在上下文中运行 future，未设置的字段沿用外层作用域的值
+    pub fn scope<F: Future>(self, future: F) -> impl Future<Output = F::Output> {
+        LOG_CONTEXT.scope(self.inherit(), future)
+    }
+
+    // 同步代码使用的作用域
+    pub fn sync_scope<R>(self, f: impl FnOnce() -> R) -> R {
+        LOG_CONTEXT.sync_scope(self.inherit(), f)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == LogContext::default()
+    }
+
+    // 按输出顺序列出已设置的字段
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        if let Some(uuid) = self.uuid {
+            fields.push(("uuid", uuid.to_string()));
+        }
+        if let Some(session_id) = self.session_id {
+            fields.push(("session_id", session_id.to_string()));
+        }
+        if let Some(request_id) = self.request_id {
+            fields.push(("request_id", request_id.to_string()));
+        }
+        if let Some(peer_addr) = self.peer_addr {
+            fields.push(("peer_addr", peer_addr.to_string()));
+        }
+        fields
+    }
+
+    fn inherit(self) -> LogContext {
+        let Some(outer) = LogContext::current() else {
+            return self;
+        };
+        LogContext {
+            uuid: self.uuid.or(outer.uuid),
+            session_id: self.session_id.or(outer.session_id),
+            request_id: self.request_id.or(outer.request_id),
+            peer_addr: self.peer_addr.or(outer.peer_addr),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use log::LevelFilter;
+use std::str::FromStr;
+
+// 读取过滤指令的环境变量
+pub const LOG_ENV: &str = "RUMMY_LOG";
+
+// RUST_LOG 风格的级别指令，如 "rummy::transport=debug,rummy::encrypt=warn,info"
+#[derive(Clone, Debug)]
+pub struct LevelDirectives {
+    default: LevelFilter,
+    // 按前缀长度降序排列，优先匹配最具体的指令
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl LevelDirectives {
+    pub fn new(default: LevelFilter) -> Self {
+        LevelDirectives {
+            default,
+            targets: Vec::new(),
+        }
+    }
+
+    // 未带目标的指令覆盖默认级别，无法解析的部分忽略
+    pub fn parse(spec: &str, default: LevelFilter) -> Self {
+        let mut directives = Self::new(default);
+        for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            match part.split_once('=') {
+                Some((target, level)) => match LevelFilter::from_str(level.trim()) {
+                    Ok(level) => directives.add(target.trim(), level),
+                    Err(_) => eprintln!("Ignoring invalid log directive: {}", part),
+                },
+                None => match LevelFilter::from_str(part) {
+                    Ok(level) => directives.default = level,
+                    // 只写目标名时打开该目标的全部日志
+                    Err(_) => directives.add(part, LevelFilter::Trace),
+                },
+            }
+        }
+        directives
+    }
+
+    pub fn from_env(default: LevelFilter) -> Option<Self> {
+        std::env::var(LOG_ENV).ok().map(|spec| Self::parse(&spec, default))
+    }
+
+    pub fn set_default(&mut self, level: LevelFilter) {
+        self.default = level;
+    }
+
+    pub fn add(&mut self, target: &str, level: LevelFilter) {
+        self.targets.retain(|(prefix, _)| prefix != target);
+        self.targets.push((target.to_string(), level));
+        self.targets.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+    }
+
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    // 所有指令中最宽松的级别，用于 log::set_max_level
+    pub fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Local};
+use log::kv::{Error, Key, Value, VisitSource};
+use log::{Level, Record};
+use std::fmt::Write;
+use super::LogContext;
+
+// 日志输出格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    // [时间][级别] 消息 key=value
+    Text,
+    // 每行一个 JSON 对象
+    Json,
+}
+
+// 调用线程上采集的日志快照，格式化在后台线程完成
+pub struct LogRecord {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub target: String,
+    pub module_path: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub thread: Option<String>,
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+    // 记录时所在任务的日志上下文
+    pub context: Option<LogContext>,
+}
+
+impl LogRecord {
+    pub fn capture(record: &Record) -> Self {
+        let mut fields = FieldCollector(Vec::new());
+        let _ = record.key_values().visit(&mut fields);
+        LogRecord {
+            time: Local::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            module_path: record.module_path().map(str::to_string),
+            file: record.file().map(str::to_string),
+            line: record.line(),
+            thread: std::thread::current().name().map(str::to_string),
+            message: record.args().to_string(),
+            fields: fields.0,
+            context: LogContext::current(),
+        }
+    }
+
+    // 日志系统自身产生的记录，如队列溢出统计
+    pub(crate) fn internal(level: Level, message: String) -> Self {
+        LogRecord {
+            time: Local::now(),
+            level,
+            target: "rummy::logger".to_string(),
+            module_path: Some(module_path!().to_string()),
+            file: None,
+            line: None,
+            thread: std::thread::current().name().map(str::to_string),
+            message,
+            fields: Vec::new(),
+            context: None,
+        }
+    }
+}
+
+struct FieldCollector(Vec<(String, String)>);
+
+impl<'kvs> VisitSource<'kvs> for FieldCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        self.0.push((key.as_str().to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+impl LogFormat {
+    pub fn format(&self, record: &LogRecord) -> String {
+        match self {
+            LogFormat::Text => format_text(record),
+            LogFormat::Json => format_json(record),
+        }
+    }
+}
+
+fn format_text(record: &LogRecord) -> String {
+    let mut msg = format!("[{}][{}] ", record.time.format("%Y-%m-%d %H:%M:%S"), record.level);
+    if let Some(context) = record.context.as_ref().filter(|context| !context.is_empty()) {
+        msg.push('[');
+        for (i, (key, value)) in context.fields().iter().enumerate() {
+            if i > 0 {
+                msg.push(' ');
+            }
+            let _ = write!(msg, "{}={}", key, value);
+        }
+        msg.push_str("] ");
+    }
+    msg.push_str(&record.message);
+    for (key, value) in &record.fields {
+        let _ = write!(msg, " {}={}", key, value);
+    }
+    msg.push('\n');
+    msg
+}
+
+fn format_json(record: &LogRecord) -> String {
+    let mut msg = String::with_capacity(256);
+    msg.push('{');
+    push_field(&mut msg, "timestamp", &record.time.to_rfc3339());
+    msg.push(',');
+    push_field(&mut msg, "level", record.level.as_str());
+    msg.push(',');
+    push_field(&mut msg, "target", &record.target);
+    if let Some(module_path) = &record.module_path {
+        msg.push(',');
+        push_field(&mut msg, "module_path", module_path);
+    }
+    if let Some(file) = &record.file {
+        msg.push(',');
+        match record.line {
+            Some(line) => push_field(&mut msg, "file", &format!("{}:{}", file, line)),
+            None => push_field(&mut msg, "file", file),
+        }
+    }
+    if let Some(thread) = &record.thread {
+        msg.push(',');
+        push_field(&mut msg, "thread", thread);
+    }
+    if let Some(context) = &record.context {
+        for (key, value) in context.fields() {
+            msg.push(',');
+            push_field(&mut msg, key, &value);
+        }
+    }
+    msg.push(',');
+    push_field(&mut msg, "message", &record.message);
+    if !record.fields.is_empty() {
+        msg.push_str(",\"fields\":{");
+        for (i, (key, value)) in record.fields.iter().enumerate() {
+            if i > 0 {
+                msg.push(',');
+            }
+            push_field(&mut msg, key, value);
+        }
+        msg.push('}');
+    }
+    msg.push_str("}\n");
+    msg
+}
+
+fn push_field(msg: &mut String, key: &str, value: &str) {
+    push_json_str(msg, key);
+    msg.push(':');
+    push_json_str(msg, value);
+}
+
+fn push_json_str(msg: &mut String, value: &str) {
+    msg.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => msg.push_str("\\\""),
+            '\\' => msg.push_str("\\\\"),
+            '\n' => msg.push_str("\\n"),
+            '\r' => msg.push_str("\\r"),
+            '\t' => msg.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(msg, "\\u{:04x}", c as u32);
+            }
+            c => msg.push(c),
+        }
+    }
+    msg.push('"');
+}
@@ -1,113 +1,363 @@
-use chrono::Local;
-use log::Level;
-use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
-use std::fs::OpenOptions;
-use std::io::{self, Write};
-use std::sync::{mpsc, OnceLock};
-use std::thread;
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock, RwLock};
+use std::thread::{self, JoinHandle};
+
+mod rotation;
+mod format;
+mod filter;
+mod console;
+mod queue;
+mod sink;
+#[cfg(unix)]
+mod syslog;
+mod context;
+mod remote;
+
+pub use rotation::{RotatingFile, Rotation, RotationNaming, RotationPeriod};
+pub use format::{LogFormat, LogRecord};
+pub use filter::{LevelDirectives, LOG_ENV};
+pub use console::ConsoleConfig;
+pub(crate) use console::reserve_stdout;
+pub use queue::{OverflowPolicy, QueueConfig};
+pub use sink::{ConsoleSink, FileSink, LogSink, RingBuffer, RingBufferSink, SinkConfig, WriterSink};
+#[cfg(unix)]
+pub use syslog::{SyslogSink, SYSLOG_SOCKET};
+pub use context::LogContext;
+pub use remote::{LogCollector, RemoteSink, RemoteSinkConfig};
+
+use queue::LogQueue;
+
+// 初始化日志系统的错误
+#[derive(Debug)]
+pub enum LoggerError {
+    // 打开日志文件失败
+    Io(io::Error),
+    // 已经设置过全局 logger
+    SetLogger(SetLoggerError),
+}
+
+impl std::fmt::Display for LoggerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoggerError::Io(e) => write!(f, "failed to open log file: {}", e),
+            LoggerError::SetLogger(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for LoggerError {}
+
+// 日志配置
+pub struct LoggerConfig {
+    // 默认级别，可被 directives 或 RUMMY_LOG 环境变量中的指令覆盖
+    pub level: LevelFilter,
+    pub directives: Option<String>,
+    pub file_path: PathBuf,
+    pub rotation: Rotation,
+    // 文件输出格式，控制台始终为文本
+    pub format: LogFormat,
+    pub console: ConsoleConfig,
+    // 文件与控制台队列各自的容量与溢出策略
+    pub queue: QueueConfig,
+    // 额外的输出目标，与日志文件在同一后台线程写出
+    pub sinks: Vec<SinkConfig>,
+}
+
+impl LoggerConfig {
+    pub fn new(level: LevelFilter, file_path: impl Into<PathBuf>) -> Self {
+        LoggerConfig {
+            level,
+            directives: None,
+            file_path: file_path.into(),
+            rotation: Rotation::default(),
+            format: LogFormat::Text,
+            console: ConsoleConfig::default(),
+            queue: QueueConfig::default(),
+            sinks: Vec::new(),
+        }
+    }
+}
+
+// 发往后台线程的命令
+enum LogCommand {
+    Record(Arc<LogRecord>),
+    // 处理完此前排队的日志后回执
+    Flush(mpsc::Sender<()>),
+    // 仅写文件线程处理，path 只作用于日志文件
+    Reopen(Option<PathBuf>, mpsc::Sender<io::Result<()>>),
+    SetFormat(LogFormat),
+    Shutdown,
+}
 
 struct DefaultLogger {
-    level_filter: LevelFilter,
-    file_sender: mpsc::Sender<String>,
-    console_sender: mpsc::Sender<(Level, String)>,
+    directives: RwLock<LevelDirectives>,
+    console_enabled: AtomicBool,
+    file_queue: Arc<LogQueue>,
+    console_queue: Arc<LogQueue>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl Log for DefaultLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= self.level_filter.to_level().unwrap_or(Level::Error)
+        metadata.level() <= self.directives.read().unwrap().level_for(metadata.target())
     }
 
     fn log(&self, record: &Record) {
-        let now = Local::now();
-        let msg = format!(
-            "[{}][{}] {}\n",
-            now.format("%Y-%m-%d %H:%M:%S"),
-            record.level(),
-            record.args()
-        );
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // 只在调用线程采集数据，格式化交给后台线程
+        let record = Arc::new(LogRecord::capture(record));
 
         // 发送日志给写文件线程（全部写）
-        let _ = self.file_sender.send(msg.clone());
+        self.file_queue.push(LogCommand::Record(Arc::clone(&record)));
 
-        // 控制台打印策略：
-        // Error 级别立即打印，其它等级缓存10条批量打印
-        let _ = self.console_sender.send((record.level(), msg));
+        // 控制台按 ConsoleConfig 的间隔与立即打印级别批量输出
+        if self.console_enabled.load(Ordering::Relaxed) {
+            self.console_queue.push(LogCommand::Record(record));
+        }
     }
 
+    // 阻塞直到文件与控制台队列中已有的日志全部写出
     fn flush(&self) {
-        // 如果需要实现 flush，可以发送特殊消息让线程强制刷新
+        let (ack_sender, ack_receiver) = mpsc::channel();
+        let mut pending = 0;
+        for queue in [&self.file_queue, &self.console_queue] {
+            if queue.push(LogCommand::Flush(ack_sender.clone())) {
+                pending += 1;
+            }
+        }
+        drop(ack_sender);
+        for _ in 0..pending {
+            // 线程已退出时回执发送端随之丢弃，不会永久阻塞
+            if ack_receiver.recv().is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl DefaultLogger {
+    fn update_directives(&self, update: impl FnOnce(&mut LevelDirectives)) {
+        let mut directives = self.directives.write().unwrap();
+        update(&mut directives);
+        log::set_max_level(directives.max_level());
+    }
+
+    fn reopen(&self, path: Option<PathBuf>) -> io::Result<()> {
+        let (ack_sender, ack_receiver) = mpsc::channel();
+        let stopped = || io::Error::other("logger has been shut down");
+        if !self.file_queue.push(LogCommand::Reopen(path, ack_sender)) {
+            return Err(stopped());
+        }
+        ack_receiver.recv().map_err(|_| stopped())?
+    }
+
+    fn shutdown(&self) {
+        self.flush();
+        self.file_queue.push(LogCommand::Shutdown);
+        self.console_queue.push(LogCommand::Shutdown);
+        for handle in self.workers.lock().unwrap().drain(..) {
+            let _ = handle.join();
+        }
     }
 }
 
 static LOGGER: OnceLock<DefaultLogger> = OnceLock::new();
 
-pub fn init_logger(level: LevelFilter, file_path: &str) -> Result<(), SetLoggerError> {
-    let (file_tx, file_rx) = mpsc::channel::<String>();
-    let (console_tx, console_rx) = mpsc::channel::<(Level, String)>();
-
-    // 打开日志文件
-    let file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(file_path)
-        .expect("Failed to open log file");
-
-    // 后台写文件线程（同步简单版）
-    thread::spawn(move || {
-        let mut file = file;
-        for line in file_rx {
-            if let Err(e) = file.write_all(line.as_bytes()) {
-                eprintln!("Log write error: {}", e);
-            }
+// 由 init_logger 与 init_logger_with 返回，离开作用域时刷新日志并等待后台线程退出
+// 运行时调整配置通过 handle 取得的 LoggerHandle
+#[must_use = "dropping the guard shuts the logger down"]
+pub struct LoggerGuard {
+    _private: (),
+}
+
+impl LoggerGuard {
+    pub fn handle(&self) -> LoggerHandle {
+        LoggerHandle { _private: () }
+    }
+}
+
+impl Drop for LoggerGuard {
+    fn drop(&mut self) {
+        shutdown_logger();
+    }
+}
+
+// 运行时调整日志配置，可随意复制
+#[derive(Clone)]
+pub struct LoggerHandle {
+    _private: (),
+}
+
+impl LoggerHandle {
+    // 修改默认级别，按目标设置的指令保持不变
+    pub fn set_level(&self, level: LevelFilter) {
+        if let Some(logger) = LOGGER.get() {
+            logger.update_directives(|directives| directives.set_default(level));
+        }
+    }
+
+    // 用新的指令整体替换当前级别配置
+    pub fn set_directives(&self, spec: &str, default: LevelFilter) {
+        if let Some(logger) = LOGGER.get() {
+            logger.update_directives(|directives| *directives = LevelDirectives::parse(spec, default));
         }
-        let _ = file.flush();
+    }
+
+    // 按原路径重新打开文件，适合收到 SIGHUP 时调用
+    pub fn reopen(&self) -> io::Result<()> {
+        self.logger()?.reopen(None)
+    }
+
+    // 切换到新的日志文件，其它输出目标按原配置重新打开
+    pub fn set_file(&self, path: impl Into<PathBuf>) -> io::Result<()> {
+        self.logger()?.reopen(Some(path.into()))
+    }
+
+    pub fn set_format(&self, format: LogFormat) {
+        if let Some(logger) = LOGGER.get() {
+            logger.file_queue.push(LogCommand::SetFormat(format));
+        }
+    }
+
+    pub fn set_console(&self, enabled: bool) {
+        if let Some(logger) = LOGGER.get() {
+            logger.console_enabled.store(enabled, Ordering::Relaxed);
+        }
+    }
+
+    fn logger(&self) -> io::Result<&'static DefaultLogger> {
+        LOGGER.get().ok_or_else(|| io::Error::other("logger is not initialized"))
+    }
+}
+
+// 刷新并停止后台线程，此后的日志会被丢弃
+pub fn shutdown_logger() {
+    if let Some(logger) = LOGGER.get() {
+        logger.shutdown();
+    }
+}
+
+pub fn init_logger(level: LevelFilter, file_path: &str) -> Result<LoggerGuard, LoggerError> {
+    init_logger_with(LoggerConfig::new(level, file_path))
+}
+
+pub fn init_logger_with(config: LoggerConfig) -> Result<LoggerGuard, LoggerError> {
+    install(config)?;
+    Ok(LoggerGuard { _private: () })
+}
+
+fn install(config: LoggerConfig) -> Result<(), LoggerError> {
+    // 环境变量优先于代码中传入的指令
+    let directives = LevelDirectives::from_env(config.level).unwrap_or_else(|| match &config.directives {
+        Some(spec) => LevelDirectives::parse(spec, config.level),
+        None => LevelDirectives::new(config.level),
     });
+    let max_level = directives.max_level();
+    let format = config.format;
+    let console = config.console;
+    let console_enabled = console.enabled;
+    let file_queue = Arc::new(LogQueue::new(config.queue));
+    let console_queue = Arc::new(LogQueue::new(config.queue));
+
+    // 打开日志文件，作为第一个输出目标
+    let file = FileSink::open(&config.file_path, config.rotation).map_err(LoggerError::Io)?;
+    let mut sinks = vec![SinkConfig {
+        level: LevelFilter::Trace,
+        format,
+        sink: Box::new(file),
+    }];
+    sinks.extend(config.sinks);
+
+    // 后台写文件线程（同步简单版），切分与压缩也在该线程完成
+    let queue = Arc::clone(&file_queue);
+    let file_handle = thread::spawn(move || run_sink_writer(sinks, &queue));
 
     // 后台控制台打印线程，批量处理
-    thread::spawn(move || {
-        let mut buffer:Vec<String> = Vec::with_capacity(10);
-        loop {
-            // 阻塞接收
-            match console_rx.recv() {
-                Ok((level, msg)) => {
-                    if level == Level::Error {
-                        // Error 立即打印
-                        let _ = io::stderr().write_all(msg.as_bytes());
-                        // 如果缓冲区里有积累的，先批量打印
-                        if !buffer.is_empty() {
-                            for m in buffer.drain(..) {
-                                let _ = io::stderr().write_all(m.as_bytes());
-                            }
-                        }
-                    } else {
-                        // 普通等级缓冲
-                        buffer.push(msg);
-                        if buffer.len() >= 10 {
-                            for m in buffer.drain(..) {
-                                let _ = io::stderr().write_all(m.as_bytes());
-                            }
-                        }
-                    }
-                }
-                Err(_) => {
-                    // 发送端关闭，打印剩余
-                    for m in buffer.drain(..) {
-                        let _ = io::stderr().write_all(m.as_bytes());
+    let queue = Arc::clone(&console_queue);
+    let console_handle = thread::spawn(move || console::run_console_writer(console, &queue));
+
+    let logger = DefaultLogger {
+        directives: RwLock::new(directives),
+        console_enabled: AtomicBool::new(console_enabled),
+        file_queue,
+        console_queue,
+        workers: Mutex::new(vec![file_handle, console_handle]),
+    };
+
+    // 已经初始化过时停掉本次启动的后台线程
+    if let Err(rejected) = LOGGER.set(logger) {
+        rejected.shutdown();
+    }
+    log::set_logger(LOGGER.get().unwrap()).map_err(LoggerError::SetLogger)?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+// sinks[0] 为日志文件，SetFormat 与带路径的 Reopen 只作用于它
+fn run_sink_writer(mut sinks: Vec<SinkConfig>, queue: &LogQueue) {
+    let closing = queue.close_on_drop();
+    let mut next = Some(queue.pop());
+    while let Some(command) = next {
+        // 队列恢复后补记溢出期间丢弃的条数
+        let dropped = queue.take_dropped();
+        if dropped > 0 {
+            let record = LogRecord::internal(Level::Warn, format!("{} log messages dropped: queue full", dropped));
+            write_record(&mut sinks, &record);
+        }
+        match command {
+            LogCommand::Record(record) => write_record(&mut sinks, &record),
+            LogCommand::Flush(ack) => {
+                flush_sinks(&mut sinks);
+                let _ = ack.send(());
+            }
+            LogCommand::Reopen(path, ack) => {
+                let mut result = sinks[0].sink.reopen(path);
+                for entry in &mut sinks[1..] {
+                    if let Err(e) = entry.sink.reopen(None) {
+                        result = result.and(Err(e));
                     }
-                    break;
                 }
+                let _ = ack.send(result);
             }
+            LogCommand::SetFormat(format) => sinks[0].format = format,
+            LogCommand::Shutdown => break,
         }
-    });
+        // 先写完已排队的日志，队列空了再统一刷新
+        next = match queue.try_pop() {
+            Some(command) => Some(command),
+            None => {
+                flush_sinks(&mut sinks);
+                Some(queue.pop())
+            }
+        };
+    }
+    drop(closing);
+    flush_sinks(&mut sinks);
+}
 
-    let logger = DefaultLogger {
-        level_filter: level,
-        file_sender: file_tx,
-        console_sender: console_tx,
-    };
+fn write_record(sinks: &mut [SinkConfig], record: &LogRecord) {
+    // 同一格式只格式化一次
+    let mut text = None;
+    let mut json = None;
+    for entry in sinks.iter_mut().filter(|entry| record.level <= entry.level) {
+        let cache = match entry.format {
+            LogFormat::Text => &mut text,
+            LogFormat::Json => &mut json,
+        };
+        let line = cache.get_or_insert_with(|| entry.format.format(record));
+        if let Err(e) = entry.sink.write(record, line) {
+            eprintln!("Log write error: {}", e);
+        }
+    }
+}
 
-    let _ = LOGGER.set(logger);
-    log::set_logger(LOGGER.get().unwrap())?;
-    log::set_max_level(level);
-    Ok(())
-}
\ No newline at end of file
+fn flush_sinks(sinks: &mut [SinkConfig]) {
+    for entry in sinks {
+        let _ = entry.sink.flush();
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+use super::LogCommand;
+
+// 队列满时的处理策略
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    // 阻塞调用方直到有空位
+    Block,
+    // 丢弃新来的日志
+    DropNewest,
+    // 丢弃最早排队的日志
+    DropOldest,
+}
+
+// 日志队列配置，capacity 为 None 时不限长度
+#[derive(Clone, Copy, Debug)]
+pub struct QueueConfig {
+    pub capacity: Option<usize>,
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        QueueConfig {
+            capacity: Some(65536),
+            overflow: OverflowPolicy::Block,
+        }
+    }
+}
+
+struct QueueState {
+    commands: VecDeque<LogCommand>,
+    // 自上次取走后被丢弃的日志条数
+    dropped: u64,
+    closed: bool,
+}
+
+// 调用线程与后台线程之间的有界队列，控制命令不受容量限制
+pub(super) struct LogQueue {
+    config: QueueConfig,
+    state: Mutex<QueueState>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+impl LogQueue {
+    pub(super) fn new(config: QueueConfig) -> Self {
+        LogQueue {
+            config,
+            state: Mutex::new(QueueState {
+                commands: VecDeque::new(),
+                dropped: 0,
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    // 队列已关闭时返回 false
+    pub(super) fn push(&self, command: LogCommand) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if let (LogCommand::Record(_), Some(capacity)) = (&command, self.config.capacity) {
+            while !state.closed && state.commands.len() >= capacity {
+                match self.config.overflow {
+                    OverflowPolicy::Block => state = self.not_full.wait(state).unwrap(),
+                    OverflowPolicy::DropNewest => {
+                        state.dropped += 1;
+                        return true;
+                    }
+                    OverflowPolicy::DropOldest => {
+                        match state.commands.iter().position(|queued| matches!(queued, LogCommand::Record(_))) {
+                            Some(index) => {
+                                state.commands.remove(index);
+                                state.dropped += 1;
+                            }
+                            // 队列里只有控制命令，直接放行
+                            None => break,
+                        }
+                    }
+                }
+            }
+        }
+        if state.closed {
+            return false;
+        }
+        state.commands.push_back(command);
+        self.not_empty.notify_one();
+        true
+    }
+
+    pub(super) fn pop(&self) -> LogCommand {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(command) = state.commands.pop_front() {
+                self.not_full.notify_one();
+                return command;
+            }
+            state = self.not_empty.wait(state).unwrap();
+        }
+    }
+
+    pub(super) fn try_pop(&self) -> Option<LogCommand> {
+        let command = self.state.lock().unwrap().commands.pop_front();
+        if command.is_some() {
+            self.not_full.notify_one();
+        }
+        command
+    }
+
+    pub(super) fn pop_timeout(&self, timeout: Duration) -> Option<LogCommand> {
+        let state = self.state.lock().unwrap();
+        let (mut state, _) = self
+            .not_empty
+            .wait_timeout_while(state, timeout, |state| state.commands.is_empty())
+            .unwrap();
+        let command = state.commands.pop_front();
+        if command.is_some() {
+            self.not_full.notify_one();
+        }
+        command
+    }
+
+    pub(super) fn take_dropped(&self) -> u64 {
+        std::mem::take(&mut self.state.lock().unwrap().dropped)
+    }
+
+    // 后台线程退出时关闭，之后的日志直接丢弃，阻塞中的调用方被唤醒
+    pub(super) fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        state.commands.clear();
+        self.not_full.notify_all();
+    }
+
+    // 后台线程开始时持有，线程正常退出或 panic 时都会关闭队列
+    pub(super) fn close_on_drop(&self) -> CloseGuard<'_> {
+        CloseGuard(self)
+    }
+}
+
+pub(super) struct CloseGuard<'a>(&'a LogQueue);
+
+impl Drop for CloseGuard<'_> {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::LogRecord;
+    use log::Level;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn queue(capacity: usize, overflow: OverflowPolicy) -> Arc<LogQueue> {
+        Arc::new(LogQueue::new(QueueConfig {
+            capacity: Some(capacity),
+            overflow,
+        }))
+    }
+
+    fn record(message: &str) -> LogCommand {
+        LogCommand::Record(Arc::new(LogRecord::internal(Level::Info, message.to_string())))
+    }
+
+    fn messages(queue: &LogQueue) -> Vec<String> {
+        std::iter::from_fn(|| queue.try_pop())
+            .filter_map(|command| match command {
+                LogCommand::Record(record) => Some(record.message.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn drop_newest_keeps_queued_records() {
+        let queue = queue(2, OverflowPolicy::DropNewest);
+        for message in ["a", "b", "c", "d"] {
+            assert!(queue.push(record(message)));
+        }
+        assert_eq!(queue.take_dropped(), 2);
+        assert_eq!(queue.take_dropped(), 0);
+        assert_eq!(messages(&queue), ["a", "b"]);
+    }
+
+    #[test]
+    fn drop_oldest_keeps_latest_records() {
+        let queue = queue(2, OverflowPolicy::DropOldest);
+        assert!(queue.push(LogCommand::Shutdown));
+        for message in ["a", "b", "c"] {
+            assert!(queue.push(record(message)));
+        }
+        // 控制命令不会被挤掉
+        assert_eq!(queue.take_dropped(), 2);
+        assert!(matches!(queue.try_pop(), Some(LogCommand::Shutdown)));
+        assert_eq!(messages(&queue), ["c"]);
+    }
+
+    #[test]
+    fn block_waits_for_space() {
+        let queue = queue(1, OverflowPolicy::Block);
+        assert!(queue.push(record("a")));
+        let producer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.push(record("b")))
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!producer.is_finished());
+
+        assert_eq!(messages(&queue), ["a"]);
+        assert!(producer.join().unwrap());
+        assert_eq!(messages(&queue), ["b"]);
+        assert_eq!(queue.take_dropped(), 0);
+    }
+
+    #[test]
+    fn writer_panic_releases_blocked_producers() {
+        let queue = queue(1, OverflowPolicy::Block);
+        assert!(queue.push(record("a")));
+        let producer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || queue.push(record("b")))
+        };
+        let writer = {
+            let queue = Arc::clone(&queue);
+            thread::spawn(move || {
+                let _closing = queue.close_on_drop();
+                thread::sleep(Duration::from_millis(50));
+                panic!("sink failed");
+            })
+        };
+        assert!(writer.join().is_err());
+        assert!(!producer.join().unwrap());
+        assert!(!queue.push(record("c")));
+    }
+}
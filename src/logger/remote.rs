@@ -0,0 +1,351 @@
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use bytes::{BufMut, Bytes, BytesMut};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::protocol::{MsgType, Packet, PacketHeader};
+use crate::transport::{TcpClientTransport, Transport, TransportError};
+use super::{LogRecord, LogSink, RotatingFile, Rotation};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// 转发线程名，该线程上（包括其中的传输任务）产生的日志不再转发
+const SHIPPER_THREAD: &str = "rummy-log-shipper";
+// 关闭时等待收集端确认的最长时间
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+// 日志转发配置
+#[derive(Clone, Debug)]
+pub struct RemoteSinkConfig {
+    // 收集端按该名称分文件保存
+    pub agent: String,
+    // 每个包最多携带的日志条数
+    pub batch_size: usize,
+    // 不足一批时的发送间隔
+    pub flush_interval: Duration,
+    // 最多缓存的日志条数，包括尚未发送与已发送未确认的，超出后丢弃最早的
+    pub max_buffered: usize,
+    pub reconnect_interval: Duration,
+}
+
+impl RemoteSinkConfig {
+    pub fn new(agent: impl Into<String>) -> Self {
+        RemoteSinkConfig {
+            agent: agent.into(),
+            batch_size: 100,
+            flush_interval: Duration::from_secs(1),
+            max_buffered: 10000,
+            reconnect_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+// 通过 TcpClientTransport 把日志批量转发给 LogCollector
+// 每批日志以 Log 包发送，收集端回复同 request_id 的 Reply 后才算送达，断线重连后重发未确认的批次
+pub struct RemoteSink {
+    sender: Option<mpsc::UnboundedSender<String>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl RemoteSink {
+    // 连接在后台线程中建立，收集端暂不可达时先缓存日志
+    pub fn connect(addr: impl Into<String>, config: RemoteSinkConfig) -> io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let shipper = Shipper::new(addr.into(), config);
+        let handle = thread::Builder::new()
+            .name(SHIPPER_THREAD.to_string())
+            .spawn(move || runtime.block_on(shipper.run(receiver)))?;
+        Ok(RemoteSink {
+            sender: Some(sender),
+            handle: Some(handle),
+        })
+    }
+}
+
+impl LogSink for RemoteSink {
+    fn write(&mut self, record: &LogRecord, line: &str) -> io::Result<()> {
+        // 转发线程的连接与断开日志转发出去会引起新的发送，断线重连时不断自我放大
+        if record.thread.as_deref() == Some(SHIPPER_THREAD) {
+            return Ok(());
+        }
+        if let Some(sender) = &self.sender {
+            sender.send(line.to_string()).map_err(|_| io::Error::other("log shipper has stopped"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RemoteSink {
+    // 关闭发送端后等待后台线程发完剩余日志
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+struct Shipper {
+    addr: String,
+    config: RemoteSinkConfig,
+    transport: Option<TcpClientTransport>,
+    pending: VecDeque<String>,
+    // 已发送但未确认的批次 (批次号, 日志条数, 内容)
+    unacked: VecDeque<(u64, usize, Bytes)>,
+    unacked_lines: usize,
+    next_batch: u64,
+    next_attempt: Instant,
+}
+
+impl Shipper {
+    fn new(addr: String, config: RemoteSinkConfig) -> Self {
+        Shipper {
+            addr,
+            config,
+            transport: None,
+            pending: VecDeque::new(),
+            unacked: VecDeque::new(),
+            unacked_lines: 0,
+            next_batch: 1,
+            next_attempt: Instant::now(),
+        }
+    }
+
+    async fn run(mut self, mut receiver: mpsc::UnboundedReceiver<String>) {
+        let mut ticker = tokio::time::interval(self.config.flush_interval);
+        loop {
+            tokio::select! {
+                line = receiver.recv() => match line {
+                    Some(line) => {
+                        self.make_room();
+                        self.pending.push_back(line);
+                        if self.pending.len() < self.config.batch_size {
+                            continue;
+                        }
+                    }
+                    None => break,
+                },
+                incoming = next_packet(&mut self.transport) => {
+                    match incoming {
+                        Some(packet) => self.ack(&packet),
+                        // 收集端断开，未确认的批次留待重连后重发
+                        None => self.transport = None,
+                    }
+                    continue;
+                }
+                _ = ticker.tick() => {}
+            }
+            self.ship().await;
+        }
+
+        // 只在仍连接时尽力发完，避免收集端不可达时阻塞退出
+        if self.transport.is_some() {
+            self.ship().await;
+            let _ = tokio::time::timeout(SHUTDOWN_TIMEOUT, self.wait_acked()).await;
+        }
+        if let Some(mut transport) = self.transport.take() {
+            let _ = transport.close().await;
+        }
+    }
+
+    async fn ship(&mut self) {
+        if self.transport.is_none() {
+            self.reconnect().await;
+        }
+        let Some(transport) = &self.transport else {
+            return;
+        };
+        while !self.pending.is_empty() {
+            let count = self.pending.len().min(self.config.batch_size);
+            let mut payload = BytesMut::new();
+            payload.put_slice(self.config.agent.as_bytes());
+            payload.put_u8(b'\n');
+            for line in self.pending.drain(..count) {
+                payload.put_slice(line.as_bytes());
+            }
+            let batch = self.next_batch;
+            self.next_batch += 1;
+            let payload = payload.freeze();
+            self.unacked.push_back((batch, count, payload.clone()));
+            self.unacked_lines += count;
+            if transport.send(Uuid::nil(), log_packet(batch, payload)).await.is_err() {
+                self.transport = None;
+                return;
+            }
+        }
+    }
+
+    async fn reconnect(&mut self) {
+        if Instant::now() < self.next_attempt {
+            return;
+        }
+        self.next_attempt = Instant::now() + self.config.reconnect_interval;
+        let transport = match tokio::time::timeout(CONNECT_TIMEOUT, TcpClientTransport::connect(&self.addr)).await {
+            Ok(Ok(transport)) => transport,
+            // 转发线程的日志只写入其它输出目标，不会回到这里
+            Ok(Err(e)) => {
+                log::warn!("Log collector connect error: {:?}", e);
+                return;
+            }
+            Err(_) => {
+                log::warn!("Log collector connect timed out");
+                return;
+            }
+        };
+        for (batch, _, payload) in &self.unacked {
+            if transport.send(Uuid::nil(), log_packet(*batch, payload.clone())).await.is_err() {
+                return;
+            }
+        }
+        self.transport = Some(transport);
+    }
+
+    // 缓存已满时丢弃最早的日志，已发送未确认的批次比待发送的更早，整批丢弃
+    // 收集端保持连接却一直不确认时，缓存同样不会无限增长
+    fn make_room(&mut self) {
+        while self.pending.len() + self.unacked_lines >= self.config.max_buffered.max(1) {
+            match self.unacked.pop_front() {
+                Some((_, count, _)) => self.unacked_lines -= count,
+                None => {
+                    self.pending.pop_front();
+                }
+            }
+        }
+    }
+
+    fn ack(&mut self, packet: &Packet) {
+        if packet.header.msg_type != MsgType::Reply {
+            return;
+        }
+        if let Some(index) = self.unacked.iter().position(|(batch, ..)| *batch == packet.header.request_id) {
+            let (_, count, _) = self.unacked.remove(index).unwrap();
+            self.unacked_lines -= count;
+        }
+    }
+
+    async fn wait_acked(&mut self) {
+        while !self.unacked.is_empty() {
+            match next_packet(&mut self.transport).await {
+                Some(packet) => self.ack(&packet),
+                None => break,
+            }
+        }
+    }
+}
+
+// 未连接时永远挂起，供 select 使用
+async fn next_packet(transport: &mut Option<TcpClientTransport>) -> Option<Packet> {
+    match transport {
+        Some(transport) => transport.receive().await.map(|(_, packet)| packet),
+        None => std::future::pending().await,
+    }
+}
+
+fn log_packet(batch: u64, payload: Bytes) -> Packet {
+    let header = PacketHeader::for_request(&payload, 0, MsgType::Log, batch);
+    Packet::new(header, payload)
+}
+
+// 接收 RemoteSink 转发的日志，每个 agent 写入 <dir>/<agent>.log
+// 收集端不做认证，任何能连上的对端都可以以任意 agent 名写入日志，
+// 应只监听在可信网络中，或使用开启客户端证书校验的 TlsServerTransport
+pub struct LogCollector<T: Transport> {
+    transport: T,
+    dir: PathBuf,
+    rotation: Rotation,
+    files: HashMap<String, RotatingFile>,
+}
+
+impl<T: Transport + Send> LogCollector<T> {
+    pub fn new(transport: T, dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(LogCollector {
+            transport,
+            dir,
+            rotation: Rotation::default(),
+            files: HashMap::new(),
+        })
+    }
+
+    // 各 agent 文件的切分策略，需在 run 之前调用
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.rotation = rotation;
+    }
+
+    pub async fn run(mut self) -> Result<(), TransportError> {
+        log::info!("Log collector writing to {}", self.dir.display());
+        while let Some((uuid, packet)) = self.transport.receive().await {
+            if packet.header.msg_type != MsgType::Log {
+                log::warn!("Unexpected {:?} packet from {}", packet.header.msg_type, uuid);
+                continue;
+            }
+            let request_id = packet.header.request_id;
+            match self.store(packet.payload).await {
+                Ok(()) => {
+                    let header = PacketHeader::for_request(&[], 0, MsgType::Reply, request_id);
+                    if let Err(e) = self.transport.send(uuid, Packet::new(header, Bytes::new())).await {
+                        log::warn!("Failed to ack log batch {} from {}: {:?}", request_id, uuid, e);
+                    }
+                }
+                // 不确认，agent 重连后会重发
+                Err(e) => log::error!("Failed to store log batch {} from {}: {}", request_id, uuid, e),
+            }
+        }
+        log::warn!("Log collector stopped: transport closed");
+        let mut files = std::mem::take(&mut self.files);
+        let _ = tokio::task::spawn_blocking(move || {
+            for file in files.values_mut() {
+                let _ = file.flush();
+            }
+        })
+        .await;
+        self.transport.close().await
+    }
+
+    // 文件读写放到阻塞线程池中，文件表随任务移入移出
+    async fn store(&mut self, payload: Bytes) -> io::Result<()> {
+        let mut files = std::mem::take(&mut self.files);
+        let dir = self.dir.clone();
+        let rotation = self.rotation.clone();
+        let (files, result) = tokio::task::spawn_blocking(move || {
+            let result = store(&mut files, &dir, rotation, &payload);
+            (files, result)
+        })
+        .await
+        .map_err(io::Error::other)?;
+        self.files = files;
+        result
+    }
+}
+
+fn store(files: &mut HashMap<String, RotatingFile>, dir: &Path, rotation: Rotation, payload: &[u8]) -> io::Result<()> {
+    let split = payload
+        .iter()
+        .position(|&b| b == b'\n')
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing agent name"))?;
+    let agent = agent_file_name(&payload[..split]);
+    let file = match files.get_mut(&agent) {
+        Some(file) => file,
+        None => {
+            let path = dir.join(format!("{}.log", agent));
+            let file = RotatingFile::open(path, rotation)?;
+            files.entry(agent).or_insert(file)
+        }
+    };
+    file.write_line(&payload[split + 1..])?;
+    file.flush()
+}
+
+// agent 名称只保留安全字符，防止写到目录之外
+fn agent_file_name(name: &[u8]) -> String {
+    let name: String = String::from_utf8_lossy(name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if name.is_empty() { "unknown".to_string() } else { name }
+}
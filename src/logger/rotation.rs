@@ -0,0 +1,286 @@
+use chrono::Local;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+// 按时间切分的周期
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RotationPeriod {
+    Daily,
+    Hourly,
+}
+
+impl RotationPeriod {
+    fn key(&self) -> String {
+        match self {
+            RotationPeriod::Daily => Local::now().format("%Y-%m-%d").to_string(),
+            RotationPeriod::Hourly => Local::now().format("%Y-%m-%d-%H").to_string(),
+        }
+    }
+}
+
+// 切分后旧文件的命名方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RotationNaming {
+    // app.log.1、app.log.2 ...，数字越大越旧
+    Numbered,
+    // app.log.2025-01-01，同一周期内多次切分时追加 .1、.2
+    Dated,
+}
+
+// 日志切分策略，默认不切分
+#[derive(Clone, Debug)]
+pub struct Rotation {
+    pub max_size: Option<u64>,
+    pub period: Option<RotationPeriod>,
+    pub naming: RotationNaming,
+    // 最多保留的旧文件数量，None 表示不清理，至少为 1
+    pub max_files: Option<usize>,
+    // 切分后用 gzip 压缩旧文件
+    pub compress: bool,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation {
+            max_size: None,
+            period: None,
+            naming: RotationNaming::Numbered,
+            max_files: None,
+            compress: false,
+        }
+    }
+}
+
+impl Rotation {
+    fn enabled(&self) -> bool {
+        self.max_size.is_some() || self.period.is_some()
+    }
+}
+
+// 支持切分的日志文件，由后台写文件线程独占
+pub struct RotatingFile {
+    path: PathBuf,
+    rotation: Rotation,
+    file: BufWriter<File>,
+    size: u64,
+    period_key: Option<String>,
+}
+
+impl RotatingFile {
+    pub fn open(path: impl Into<PathBuf>, rotation: Rotation) -> io::Result<Self> {
+        if rotation.max_files == Some(0) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "max_files must be at least 1"));
+        }
+        let path = path.into();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        let period_key = rotation.period.map(|period| period.key());
+        Ok(RotatingFile {
+            path,
+            rotation,
+            file: BufWriter::new(file),
+            size,
+            period_key,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.rotation.enabled()
+            && self.should_rotate(line.len() as u64)
+            && let Err(e) = self.rotate()
+        {
+            eprintln!("Log rotation error: {}", e);
+        }
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    // 重新打开日志文件，path 为 None 时沿用原路径（外部 logrotate 移走文件后使用）
+    pub fn reopen(&mut self, path: Option<PathBuf>) -> io::Result<()> {
+        self.file.flush()?;
+        let path = path.unwrap_or_else(|| self.path.clone());
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        self.size = file.metadata()?.len();
+        self.file = BufWriter::new(file);
+        self.path = path;
+        self.period_key = self.rotation.period.map(|period| period.key());
+        Ok(())
+    }
+
+    fn should_rotate(&self, incoming: u64) -> bool {
+        if let Some(max_size) = self.rotation.max_size
+            && self.size > 0
+            && self.size + incoming > max_size
+        {
+            return true;
+        }
+        match (self.rotation.period, &self.period_key) {
+            (Some(period), Some(key)) => period.key() != *key,
+            _ => false,
+        }
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let rotated = match self.rotation.naming {
+            RotationNaming::Numbered => {
+                self.shift_numbered()?;
+                self.sibling("1")
+            }
+            RotationNaming::Dated => self.dated_target(),
+        };
+        fs::rename(&self.path, &rotated)?;
+        if self.rotation.compress {
+            compress(&rotated)?;
+        }
+        if self.rotation.naming == RotationNaming::Dated {
+            self.remove_oldest_dated()?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.file = BufWriter::new(file);
+        self.size = 0;
+        self.period_key = self.rotation.period.map(|period| period.key());
+        Ok(())
+    }
+
+    // app.log.N 依次后移一位，超出保留数量的直接删除
+    fn shift_numbered(&self) -> io::Result<()> {
+        let mut indexes: Vec<(usize, PathBuf)> = self
+            .rotated_files()?
+            .into_iter()
+            .filter_map(|path| {
+                let suffix = self.suffix_of(&path)?;
+                let index = suffix.trim_end_matches(".gz").parse().ok()?;
+                Some((index, path))
+            })
+            .collect();
+        indexes.sort_by_key(|(index, _)| std::cmp::Reverse(*index));
+
+        for (index, path) in indexes {
+            if self.rotation.max_files.is_some_and(|max| index >= max) {
+                fs::remove_file(&path)?;
+                continue;
+            }
+            let gz = if path.to_string_lossy().ends_with(".gz") { ".gz" } else { "" };
+            fs::rename(&path, self.sibling(&format!("{}{}", index + 1, gz)))?;
+        }
+        Ok(())
+    }
+
+    fn dated_target(&self) -> PathBuf {
+        // 按大小切分且未配置周期时，用精确到秒的时间命名
+        let key = self
+            .period_key
+            .clone()
+            .unwrap_or_else(|| Local::now().format("%Y-%m-%d-%H%M%S").to_string());
+        let mut target = self.sibling(&key);
+        let mut counter = 1;
+        while target.exists() || gz_path(&target).exists() {
+            target = self.sibling(&format!("{}.{}", key, counter));
+            counter += 1;
+        }
+        target
+    }
+
+    fn remove_oldest_dated(&self) -> io::Result<()> {
+        let Some(max_files) = self.rotation.max_files else {
+            return Ok(());
+        };
+        let mut files: Vec<(std::time::SystemTime, PathBuf)> = self
+            .rotated_files()?
+            .into_iter()
+            .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+            .collect();
+        if files.len() <= max_files {
+            return Ok(());
+        }
+        files.sort_by(|a, b| b.cmp(a));
+        for (_, path) in files.into_iter().skip(max_files) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    // 与当前日志文件同目录、后缀符合本切分器命名格式的旧文件
+    // app.log.bak 之类的其它文件不会被当作旧日志清理
+    fn rotated_files(&self) -> io::Result<Vec<PathBuf>> {
+        let dir = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let mut files = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if self.suffix_of(&path).is_some_and(|suffix| is_rotated_suffix(&suffix, self.rotation.naming)) {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
+
+    fn suffix_of(&self, path: &Path) -> Option<String> {
+        let base = self.path.file_name()?.to_str()?;
+        let name = path.file_name()?.to_str()?;
+        name.strip_prefix(base)?.strip_prefix('.').map(str::to_string)
+    }
+
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".");
+        name.push(suffix);
+        PathBuf::from(name)
+    }
+}
+
+// Numbered 为 N，Dated 为日期键加可选的 .N，压缩后再加 .gz
+fn is_rotated_suffix(suffix: &str, naming: RotationNaming) -> bool {
+    let suffix = suffix.strip_suffix(".gz").unwrap_or(suffix);
+    match naming {
+        RotationNaming::Numbered => is_number(suffix),
+        RotationNaming::Dated => match suffix.split_once('.') {
+            Some((key, counter)) => is_date_key(key) && is_number(counter),
+            None => is_date_key(suffix),
+        },
+    }
+}
+
+fn is_number(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+// %Y-%m-%d、%Y-%m-%d-%H 或 %Y-%m-%d-%H%M%S
+fn is_date_key(key: &str) -> bool {
+    let dashes: &[usize] = match key.len() {
+        10 => &[4, 7],
+        13 | 17 => &[4, 7, 10],
+        _ => return false,
+    };
+    key.bytes()
+        .enumerate()
+        .all(|(index, b)| if dashes.contains(&index) { b == b'-' } else { b.is_ascii_digit() })
+}
+
+fn gz_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+// 压缩为 .gz 后删除原文件
+fn compress(path: &Path) -> io::Result<()> {
+    let mut input = File::open(path)?;
+    let output = File::create(gz_path(path))?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    fs::remove_file(path)
+}
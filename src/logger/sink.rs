@@ -0,0 +1,162 @@
+use log::LevelFilter;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use super::console::stdout_reserved;
+use super::{LogFormat, LogRecord, RotatingFile, Rotation};
+
+// 日志输出目标，由后台写线程独占调用
+pub trait LogSink: Send {
+    // line 为按该输出目标的格式化结果，record 供需要级别等信息的目标使用
+    fn write(&mut self, record: &LogRecord, line: &str) -> io::Result<()>;
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    // 重新打开底层资源，path 只对文件类目标有意义
+    fn reopen(&mut self, _path: Option<PathBuf>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// 一个输出目标及其级别与格式
+pub struct SinkConfig {
+    pub level: LevelFilter,
+    pub format: LogFormat,
+    pub sink: Box<dyn LogSink>,
+}
+
+impl SinkConfig {
+    pub fn new(sink: impl LogSink + 'static) -> Self {
+        SinkConfig {
+            level: LevelFilter::Trace,
+            format: LogFormat::Text,
+            sink: Box::new(sink),
+        }
+    }
+}
+
+// 支持切分的文件
+pub struct FileSink {
+    file: RotatingFile,
+}
+
+impl FileSink {
+    pub fn open(path: impl Into<PathBuf>, rotation: Rotation) -> io::Result<Self> {
+        Ok(FileSink {
+            file: RotatingFile::open(path, rotation)?,
+        })
+    }
+}
+
+impl LogSink for FileSink {
+    fn write(&mut self, _record: &LogRecord, line: &str) -> io::Result<()> {
+        self.file.write_line(line.as_bytes())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    fn reopen(&mut self, path: Option<PathBuf>) -> io::Result<()> {
+        self.file.reopen(path)
+    }
+}
+
+// 不带缓冲的控制台输出，需要批量打印时使用 ConsoleConfig
+// stdout 被 StdioTransport 占用时改为输出到 stderr
+pub struct ConsoleSink {
+    stderr: bool,
+}
+
+impl ConsoleSink {
+    pub fn stdout() -> Self {
+        ConsoleSink { stderr: false }
+    }
+
+    pub fn stderr() -> Self {
+        ConsoleSink { stderr: true }
+    }
+}
+
+impl LogSink for ConsoleSink {
+    fn write(&mut self, _record: &LogRecord, line: &str) -> io::Result<()> {
+        if self.stderr || stdout_reserved() {
+            io::stderr().write_all(line.as_bytes())
+        } else {
+            io::stdout().write_all(line.as_bytes())
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.stderr || stdout_reserved() {
+            io::stderr().flush()
+        } else {
+            io::stdout().flush()
+        }
+    }
+}
+
+// 包装任意 Write 作为输出目标
+pub struct WriterSink<W: Write + Send> {
+    writer: W,
+}
+
+impl<W: Write + Send> WriterSink<W> {
+    pub fn new(writer: W) -> Self {
+        WriterSink { writer }
+    }
+}
+
+impl<W: Write + Send> LogSink for WriterSink<W> {
+    fn write(&mut self, _record: &LogRecord, line: &str) -> io::Result<()> {
+        self.writer.write_all(line.as_bytes())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+// 内存中保留最近若干行日志，可在任意线程查询
+#[derive(Clone)]
+pub struct RingBuffer {
+    capacity: usize,
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> Self {
+        RingBuffer {
+            capacity,
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    // 最近的 n 行，按时间先后排列
+    pub fn last(&self, n: usize) -> Vec<String> {
+        let lines = self.lines.lock().unwrap();
+        lines.iter().skip(lines.len().saturating_sub(n)).cloned().collect()
+    }
+
+    pub fn sink(&self) -> RingBufferSink {
+        RingBufferSink { buffer: self.clone() }
+    }
+}
+
+pub struct RingBufferSink {
+    buffer: RingBuffer,
+}
+
+impl LogSink for RingBufferSink {
+    fn write(&mut self, _record: &LogRecord, line: &str) -> io::Result<()> {
+        let mut lines = self.buffer.lines.lock().unwrap();
+        if lines.len() >= self.buffer.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line.trim_end_matches('\n').to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use log::Level;
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use super::{LogRecord, LogSink};
+
+// 本机 syslog 守护进程的默认套接字
+pub const SYSLOG_SOCKET: &str = "/dev/log";
+
+// facility 为 user
+const FACILITY_USER: u8 = 1;
+
+// 通过 unix 数据报套接字发送到 syslog，消息格式遵循 RFC 3164
+pub struct SyslogSink {
+    socket: UnixDatagram,
+    path: PathBuf,
+    tag: String,
+}
+
+impl SyslogSink {
+    pub fn new(tag: &str) -> io::Result<Self> {
+        Self::with_path(SYSLOG_SOCKET, tag)
+    }
+
+    pub fn with_path(path: impl AsRef<Path>, tag: &str) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(&path)?;
+        Ok(SyslogSink {
+            socket,
+            path,
+            tag: tag.to_string(),
+        })
+    }
+
+    fn send(&self, message: &[u8]) -> io::Result<()> {
+        self.socket.send(message).map(|_| ())
+    }
+}
+
+impl LogSink for SyslogSink {
+    // 时间与级别已由 syslog 头部表示，只发送日志内容，忽略按格式生成的整行
+    fn write(&mut self, record: &LogRecord, _line: &str) -> io::Result<()> {
+        let priority = FACILITY_USER * 8 + severity(record.level);
+        let message = format!(
+            "<{}>{} {}[{}]: {}",
+            priority,
+            record.time.format("%b %e %H:%M:%S"),
+            self.tag,
+            std::process::id(),
+            record.message
+        );
+        // syslog 重启后旧连接失效，重连一次后重试
+        if self.send(message.as_bytes()).is_err() {
+            self.reopen(None)?;
+            self.send(message.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn reopen(&mut self, _path: Option<PathBuf>) -> io::Result<()> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(&self.path)?;
+        self.socket = socket;
+        Ok(())
+    }
+}
+
+fn severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
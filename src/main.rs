@@ -1,7 +1,27 @@
+mod relay_cli;
+
+use std::process::ExitCode;
 use log::LevelFilter;
-use rummy::logger::init_logger;
+use rummy::logger::{init_logger_with, LoggerConfig};
+
+fn main() -> ExitCode {
+    // guard 离开作用域时刷新日志，保证退出前的日志写入文件
+    let _guard = init_logger_with(LoggerConfig::new(LevelFilter::Warn, "default.log"))
+        .expect("Logger init failed");
 
-fn main() {
-    init_logger(LevelFilter::Warn, "default.log").expect("Logger init failed");
-    println!("Hello, world!");
-}
\ No newline at end of file
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        // rummy relay <listen> <upstream>，在两种传输之间转发
+        Some("relay") => {
+            let runtime = tokio::runtime::Runtime::new().expect("Failed to start tokio runtime");
+            // 返回而不是 process::exit，guard 照常析构
+            if let Err(e) = runtime.block_on(relay_cli::run(&args[1..])) {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+        Some("help") | Some("--help") => println!("{}", relay_cli::USAGE),
+        _ => println!("Hello, world!"),
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,46 @@
+use crc32fast::Hasher;
+use xxhash_rust::xxh32::Xxh32;
+
+// 校验算法，记录在包头第 6 字节
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumAlgo {
+    Crc32 = 0u8,
+    // 支持 SSE4.2 / ARMv8 CRC 指令时使用硬件加速
+    Crc32c = 1u8,
+    XxHash32 = 2u8,
+}
+
+impl ChecksumAlgo {
+    pub fn from_u8(value: u8) -> Option<ChecksumAlgo> {
+        match value {
+            0 => Some(ChecksumAlgo::Crc32),
+            1 => Some(ChecksumAlgo::Crc32c),
+            2 => Some(ChecksumAlgo::XxHash32),
+            _ => None,
+        }
+    }
+
+    // 依次对多段数据计算校验值，避免拼接拷贝
+    pub fn hash(&self, parts: &[&[u8]]) -> u32 {
+        match self {
+            ChecksumAlgo::Crc32 => {
+                let mut hasher = Hasher::new();
+                for part in parts {
+                    hasher.update(part);
+                }
+                hasher.finalize()
+            }
+            ChecksumAlgo::Crc32c => parts
+                .iter()
+                .fold(0, |crc, part| crc32c::crc32c_append(crc, part)),
+            ChecksumAlgo::XxHash32 => {
+                let mut hasher = Xxh32::new(0);
+                for part in parts {
+                    hasher.update(part);
+                }
+                hasher.digest()
+            }
+        }
+    }
+}
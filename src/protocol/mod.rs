@@ -1,5 +1,17 @@
-const HEADER_SIZE: usize = 64;
-const MAGIC: &[u8; 4] = b"rum3";
+use bytes::{Bytes, BytesMut};
+
+mod replay;
+mod checksum;
+
+pub use replay::{ReplayConfig, ReplayGuard, SharedReplayGuard};
+pub(crate) use replay::SequenceWindow;
+pub use checksum::ChecksumAlgo;
+
+pub const HEADER_SIZE: usize = 64;
+pub(crate) const MAGIC: &[u8; 4] = b"rum3";
+// v1：校验和只覆盖 payload（CRC32）
+// v2：校验和覆盖包头（校验和字段置 0）加 payload，算法可选
+pub const PROTOCOL_VERSION: u8 = 2;
 
 // 消息类型错误
 #[derive(Debug)]
@@ -9,15 +21,27 @@ pub enum MsgError {
     ChecksumMismatch,
     UnsupportedVersion,
     InvalidMagic,
+    StalePacket,
+    ReplayedPacket,
+}
+
+// 当前毫秒时间戳，包头中的时间字段都使用该单位
+pub fn now_millis() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
 }
 
 #[repr(u8)]
-#[derive(Clone, Copy, Debug)]
-enum MsgType{
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MsgType{
     Call = 0u8,
     Reply = 1u8,
     Error = 2u8,
     Auth = 3u8,
+    Cancel = 4u8,
+    // 一批转发到收集端的日志
+    Log = 5u8,
+    // 调用在分发前已过期，不携带内容，与处理器返回的 Error 区分
+    DeadlineExceeded = 6u8,
 }
 
 #[repr(C)]
@@ -26,45 +50,76 @@ pub struct PacketHeader {
     pub magic: [u8; 4],        // 固定魔数 b"rum3"
     pub version: u8,           // 协议版本号
     pub msg_type: MsgType,     // 消息类型
-    pub reserved: [u8; 10],    // 预留字段
+    pub checksum_algo: ChecksumAlgo, // 校验算法（v2）
+    pub reserved: [u8; 9],     // 预留字段
     pub payload_len: u32,      // 消息体长度（单位：字节）
     pub session_id: u64,       // 会话 ID
     pub timestamp: u64,        // 时间戳（用于超时、认证）
-    pub checksum: u32,         // 可选：对 payload 做 CRC32 校验
-    pub _padding: [u8; 24],    // 填充到 64 字节
+    pub checksum: u32,         // 校验和，v2 在 Packet::to_bytes 时计算
+    pub request_id: u64,       // 请求 ID（Reply/Error/Cancel 引用对应的 Call）
+    pub deadline: u64,         // 调用截止时间（毫秒时间戳，0 表示不限）
+    pub sequence: u64,         // 连接内单调递增的序号（防重放，0 表示未设置）
 }
 
 impl PacketHeader {
     pub fn from_payload(payload: &[u8], session_id: u64) -> Self{
         let payload_len = payload.len() as u32;
-        let checksum = crc32fast::hash(payload);
         PacketHeader {
             magic: *MAGIC,
-            version: 1, // 假设当前版本为 1
+            version: PROTOCOL_VERSION,
             msg_type: MsgType::Call, // 默认消息类型为 Call
-            reserved: [0; 10], // 预留字段初始化为 0
+            checksum_algo: ChecksumAlgo::Crc32,
+            reserved: [0; 9], // 预留字段初始化为 0
             payload_len,
             session_id,
-            timestamp: chrono::Utc::now().timestamp_millis() as u64, // 当前时间戳
-            checksum,
-            _padding: [0; 24], // 填充到 64 字节
+            timestamp: now_millis(), // 当前时间戳
+            checksum: 0, // 发送时计算，序号等字段可能在此之后才确定
+            request_id: 0,
+            deadline: 0,
+            sequence: 0,
         }
     }
 
-    pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(HEADER_SIZE);
-        bytes.extend_from_slice(&self.magic);
-        bytes.push(self.version);
-        bytes.push(self.msg_type as u8);
-        bytes.extend_from_slice(&self.reserved);
-        bytes.extend_from_slice(&self.payload_len.to_le_bytes());
-        bytes.extend_from_slice(&self.session_id.to_le_bytes());
-        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
-        bytes.extend_from_slice(&self.checksum.to_le_bytes());
-        bytes.extend_from_slice(&self._padding);
+    // 构造指定类型、关联到某个请求的包头
+    pub fn for_request(payload: &[u8], session_id: u64, msg_type: MsgType, request_id: u64) -> Self {
+        let mut header = Self::from_payload(payload, session_id);
+        header.msg_type = msg_type;
+        header.request_id = request_id;
+        header
+    }
+
+    // 计算该包头与 payload 对应的校验和
+    pub fn compute_checksum(&self, payload: &[u8]) -> u32 {
+        if self.version == 1 {
+            return crc32fast::hash(payload);
+        }
+        let mut header = *self;
+        header.checksum = 0;
+        self.checksum_algo.hash(&[&header.encode(), payload])
+    }
+
+    // 编码到栈上数组，不分配堆内存
+    pub fn encode(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[0..4].copy_from_slice(&self.magic);
+        bytes[4] = self.version;
+        bytes[5] = self.msg_type as u8;
+        bytes[6] = self.checksum_algo as u8;
+        bytes[7..16].copy_from_slice(&self.reserved);
+        bytes[16..20].copy_from_slice(&self.payload_len.to_le_bytes());
+        bytes[20..28].copy_from_slice(&self.session_id.to_le_bytes());
+        bytes[28..36].copy_from_slice(&self.timestamp.to_le_bytes());
+        bytes[36..40].copy_from_slice(&self.checksum.to_le_bytes());
+        bytes[40..48].copy_from_slice(&self.request_id.to_le_bytes());
+        bytes[48..56].copy_from_slice(&self.deadline.to_le_bytes());
+        bytes[56..64].copy_from_slice(&self.sequence.to_le_bytes());
         bytes
     }
 
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode().to_vec()
+    }
+
     pub fn from_bytes(buf:&[u8]) -> Result<PacketHeader, MsgError> {
         // 缓冲区长度不足
         if buf.len() < HEADER_SIZE {
@@ -78,67 +133,95 @@ impl PacketHeader {
             1 => MsgType::Reply,
             2 => MsgType::Error,
             3 => MsgType::Auth,
+            4 => MsgType::Cancel,
+            5 => MsgType::Log,
+            6 => MsgType::DeadlineExceeded,
             _ => return Err(MsgError::InvalidHeader), // 无效的消息类型
         };
-        let reserved = buf[6..16].try_into().unwrap();
+        // v1 中该字节属于预留字段，恒为 0 即 CRC32
+        let checksum_algo = ChecksumAlgo::from_u8(buf[6]).ok_or(MsgError::InvalidHeader)?;
+        let reserved = buf[7..16].try_into().unwrap();
         let payload_len = u32::from_le_bytes(buf[16..20].try_into().unwrap());
         let session_id = u64::from_le_bytes(buf[20..28].try_into().unwrap());
         let timestamp = u64::from_le_bytes(buf[28..36].try_into().unwrap());
         let checksum = u32::from_le_bytes(buf[36..40].try_into().unwrap());
-        let _padding = buf[40..64].try_into().unwrap();
+        let request_id = u64::from_le_bytes(buf[40..48].try_into().unwrap());
+        let deadline = u64::from_le_bytes(buf[48..56].try_into().unwrap());
+        let sequence = u64::from_le_bytes(buf[56..64].try_into().unwrap());
 
         Ok(PacketHeader {
             magic,
             version,
             msg_type,
+            checksum_algo,
             reserved,
             payload_len,
             session_id,
             timestamp,
             checksum,
-            _padding,
+            request_id,
+            deadline,
+            sequence,
         })
     }
 }
 
+// payload 为引用计数的共享缓冲区，克隆、转发和广播都不会拷贝数据
+#[derive(Clone)]
 pub struct Packet {
     pub header: PacketHeader,
-    pub payload: Vec<u8>,
+    pub payload: Bytes,
 }
 
 impl Packet {
-    pub fn new(header: PacketHeader, payload: Vec<u8>) -> Self {
-        Packet { header, payload }
+    pub fn new(header: PacketHeader, payload: impl Into<Bytes>) -> Self {
+        Packet { header, payload: payload.into() }
+    }
+
+    // 把带校验和的包头写入可复用的写缓冲区，payload 由调用方直接写出
+    pub fn encode_header(&self, buf: &mut BytesMut) {
+        buf.extend_from_slice(&self.checked_header().encode());
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = self.header.to_bytes();
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.payload.len());
+        bytes.extend_from_slice(&self.checked_header().encode());
         bytes.extend_from_slice(&self.payload);
         bytes
     }
 
+    fn checked_header(&self) -> PacketHeader {
+        let mut header = self.header;
+        header.checksum = header.compute_checksum(&self.payload);
+        header
+    }
+
     pub fn from_bytes(buf: &[u8]) -> Result<Self, MsgError> {
-        let header = PacketHeader::from_bytes(buf)?;
+        Self::decode(Bytes::copy_from_slice(buf))
+    }
+
+    // 从完整的帧解码，payload 直接切片共享 buf，不拷贝
+    pub fn decode(buf: Bytes) -> Result<Self, MsgError> {
+        let header = PacketHeader::from_bytes(&buf)?;
         // 检查 payload 长度是否足够
         if buf.len() < HEADER_SIZE + header.payload_len as usize {
             return Err(MsgError::InvalidPayload); // 缓冲区长度不足
         }
         // 提取 payload
-        let payload = buf[HEADER_SIZE..HEADER_SIZE + header.payload_len as usize].to_vec();
-        // 检查校验和
-        if crc32fast::hash(&payload) != header.checksum {
-            return Err(MsgError::ChecksumMismatch); // 校验和不匹配
-        }
+        let payload = buf.slice(HEADER_SIZE..HEADER_SIZE + header.payload_len as usize);
         // 检查魔数是否正确
         if header.magic != *MAGIC {
             return Err(MsgError::InvalidMagic); // 魔数不匹配
         }
         // 检查版本号是否支持
-        if header.version != 1 {
+        if header.version != 1 && header.version != PROTOCOL_VERSION {
             return Err(MsgError::UnsupportedVersion); // 不支持的版本号
         }
+        // 检查校验和
+        if header.compute_checksum(&payload) != header.checksum {
+            return Err(MsgError::ChecksumMismatch); // 校验和不匹配
+        }
         // 返回 MsgBody 实例
         Ok(Packet { header, payload })
     }
 }
-
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+use crate::protocol::{now_millis, MsgError, PacketHeader};
+
+// 滑动窗口大小，即允许乱序到达的最大序号差
+const WINDOW_SIZE: u64 = 64;
+
+// 防重放配置
+#[derive(Clone, Copy, Debug)]
+pub struct ReplayConfig {
+    // 允许的时钟偏差，超出窗口的包视为过期；None 表示不检查时间戳
+    pub max_skew: Option<Duration>,
+    // 检查每条连接内的序号
+    pub check_sequence: bool,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        ReplayConfig {
+            max_skew: Some(Duration::from_secs(30)),
+            check_sequence: true,
+        }
+    }
+}
+
+// 单个会话的序号窗口，UDP 传输也用它过滤重复包
+#[derive(Default)]
+pub(crate) struct SequenceWindow {
+    highest: u64,
+    // 第 i 位表示序号 highest - i 已收到
+    bitmap: u64,
+}
+
+impl SequenceWindow {
+    // 是否已收到过或已落在窗口之外，不修改窗口
+    pub(crate) fn seen(&self, sequence: u64) -> bool {
+        if sequence > self.highest {
+            return false;
+        }
+        let offset = self.highest - sequence;
+        offset >= WINDOW_SIZE || self.bitmap & (1 << offset) != 0
+    }
+
+    pub(crate) fn accept(&mut self, sequence: u64) -> bool {
+        if sequence > self.highest {
+            let shift = sequence - self.highest;
+            self.bitmap = if shift >= WINDOW_SIZE { 0 } else { self.bitmap << shift };
+            self.bitmap |= 1;
+            self.highest = sequence;
+            return true;
+        }
+        if self.seen(sequence) {
+            return false;
+        }
+        self.bitmap |= 1 << (self.highest - sequence);
+        true
+    }
+}
+
+// 服务端所有连接共享的检查器
+pub type SharedReplayGuard = Arc<Mutex<ReplayGuard>>;
+
+// 换连接重放检查最多记住的包数，超出后不再记录新包，内存占用有上限
+const MAX_RECENT: usize = 65536;
+
+// 用于识别同一个包的头部字段：会话、序号、时间戳与校验和
+type PacketDigest = (u64, u64, u64, u32);
+
+// 接收端的过期与重放检查
+// 序号窗口按服务端分配的连接 uuid 区分，每条连接的发送端都从 1 开始编号，
+// 新连接使用新的窗口，连接结束时调用 remove 释放
+// 服务端的所有连接共享同一个检查器：已认证会话（session_id 非 0）在时钟偏差范围内
+// 收到过的包会被记住，换一条连接重放同样会被拒绝；超出偏差的包已由时间戳检查拒绝
+pub struct ReplayGuard {
+    config: ReplayConfig,
+    windows: HashMap<Uuid, SequenceWindow>,
+    // 包摘要到其过期时间（毫秒时间戳）
+    recent: HashMap<PacketDigest, u64>,
+}
+
+impl ReplayGuard {
+    pub fn new(config: ReplayConfig) -> Self {
+        ReplayGuard {
+            config,
+            windows: HashMap::new(),
+            recent: HashMap::new(),
+        }
+    }
+
+    pub fn shared(config: ReplayConfig) -> SharedReplayGuard {
+        Arc::new(Mutex::new(Self::new(config)))
+    }
+
+    // connection 为收到该包的连接
+    pub fn check(&mut self, connection: Uuid, header: &PacketHeader) -> Result<(), MsgError> {
+        let now = now_millis();
+        if let Some(max_skew) = self.config.max_skew {
+            let skew = now.abs_diff(header.timestamp);
+            if skew > max_skew.as_millis() as u64 {
+                return Err(MsgError::StalePacket);
+            }
+        }
+
+        if !self.config.check_sequence {
+            return Ok(());
+        }
+        if header.sequence == 0 {
+            return Err(MsgError::ReplayedPacket);
+        }
+        // 未认证的会话无法区分不同客户端发出的相同内容，只做连接内的检查
+        // 先查摘要再更新窗口，被拒绝的重放包不占用本连接的序号
+        let digest = (header.session_id, header.sequence, header.timestamp, header.checksum);
+        let max_skew = self.config.max_skew.filter(|_| header.session_id != 0);
+        if max_skew.is_some() && self.recent.contains_key(&digest) {
+            return Err(MsgError::ReplayedPacket);
+        }
+        if !self.windows.entry(connection).or_default().accept(header.sequence) {
+            return Err(MsgError::ReplayedPacket);
+        }
+        let Some(max_skew) = max_skew else {
+            return Ok(());
+        };
+        if self.recent.len() >= MAX_RECENT {
+            self.recent.retain(|_, &mut expires| expires >= now);
+        }
+        if self.recent.len() < MAX_RECENT {
+            self.recent.insert(digest, header.timestamp.saturating_add(max_skew.as_millis() as u64));
+        } else {
+            log::warn!("Replay cache is full, not recording packet");
+        }
+        Ok(())
+    }
+
+    // 连接结束后丢弃它的序号窗口
+    pub fn remove(&mut self, connection: Uuid) {
+        self.windows.remove(&connection);
+    }
+}
@@ -0,0 +1,147 @@
+use rummy::transport::{
+    load_certs, load_private_key, Relay, TcpClientTransport, TcpServerTransport, TlsClientTransport,
+    TlsServerTransport, Transport, WsClientTransport, WsServerTransport,
+};
+#[cfg(unix)]
+use rummy::transport::{UnixClientTransport, UnixServerTransport};
+
+pub const USAGE: &str = "usage: rummy relay <listen> <upstream> [--cert PEM --key PEM] [--ca PEM] [--server-name NAME]
+  endpoints: tcp://host:port  unix:///path  unix://@name  tls://host:port  ws://host:port/path
+  --cert/--key    certificate and key for a tls:// listener
+  --ca            trusted CA for a tls:// upstream
+  --server-name   name expected in the upstream certificate, defaults to its host";
+
+enum Endpoint {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(String),
+    Tls(String),
+    // 完整 URL，监听时拆出地址与路径
+    Ws(String),
+}
+
+impl Endpoint {
+    fn parse(value: &str) -> Result<Self, String> {
+        if let Some(addr) = value.strip_prefix("tcp://") {
+            Ok(Endpoint::Tcp(addr.to_string()))
+        } else if let Some(path) = value.strip_prefix("unix://") {
+            unix_endpoint(path)
+        } else if let Some(addr) = value.strip_prefix("tls://") {
+            Ok(Endpoint::Tls(addr.to_string()))
+        } else if value.starts_with("ws://") {
+            Ok(Endpoint::Ws(value.to_string()))
+        } else {
+            Err(format!("unsupported endpoint: {}", value))
+        }
+    }
+}
+
+#[cfg(unix)]
+fn unix_endpoint(path: &str) -> Result<Endpoint, String> {
+    Ok(Endpoint::Unix(path.to_string()))
+}
+
+#[cfg(not(unix))]
+fn unix_endpoint(_path: &str) -> Result<Endpoint, String> {
+    Err("unix:// endpoints are only supported on unix platforms".to_string())
+}
+
+#[derive(Default)]
+struct Options {
+    cert: Option<String>,
+    key: Option<String>,
+    ca: Option<String>,
+    server_name: Option<String>,
+}
+
+// 解析 relay 子命令的参数并一直运行到监听端关闭或收到 Ctrl-C
+pub async fn run(args: &[String]) -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let slot = match arg.as_str() {
+            "--cert" => &mut options.cert,
+            "--key" => &mut options.key,
+            "--ca" => &mut options.ca,
+            "--server-name" => &mut options.server_name,
+            _ => {
+                positional.push(arg);
+                continue;
+            }
+        };
+        *slot = Some(args.next().ok_or_else(|| format!("{} requires a value", arg))?.clone());
+    }
+    let [listen, upstream] = positional.as_slice() else {
+        return Err(USAGE.to_string());
+    };
+    let listen = Endpoint::parse(listen)?;
+    let upstream = Endpoint::parse(upstream)?;
+
+    match listen {
+        Endpoint::Tcp(addr) => {
+            let mut downstream = TcpServerTransport::new(addr).await.map_err(|e| format!("{:?}", e))?;
+            downstream.run();
+            relay(downstream, upstream, options).await
+        }
+        #[cfg(unix)]
+        Endpoint::Unix(path) => {
+            let mut downstream = UnixServerTransport::new(path).await.map_err(|e| format!("{:?}", e))?;
+            downstream.run();
+            relay(downstream, upstream, options).await
+        }
+        Endpoint::Tls(addr) => {
+            let (Some(cert), Some(key)) = (&options.cert, &options.key) else {
+                return Err("a tls:// listener requires --cert and --key".to_string());
+            };
+            let certs = load_certs(cert).map_err(|e| e.to_string())?;
+            let key = load_private_key(key).map_err(|e| e.to_string())?;
+            let mut downstream = TlsServerTransport::new(addr, certs, key).await.map_err(|e| format!("{:?}", e))?;
+            downstream.run();
+            relay(downstream, upstream, options).await
+        }
+        Endpoint::Ws(url) => {
+            let rest = url.trim_start_matches("ws://");
+            let (addr, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+            let path = if path.is_empty() { "/" } else { path };
+            let mut downstream = WsServerTransport::new(addr, path).await.map_err(|e| format!("{:?}", e))?;
+            downstream.run();
+            relay(downstream, upstream, options).await
+        }
+    }
+}
+
+async fn relay<D>(downstream: D, upstream: Endpoint, options: Options) -> Result<(), String>
+where
+    D: Transport + Send + Sync + 'static,
+{
+    let relay = match upstream {
+        Endpoint::Tcp(addr) => Relay::new(downstream, move || TcpClientTransport::connect(addr.clone())),
+        #[cfg(unix)]
+        Endpoint::Unix(path) => Relay::new(downstream, move || UnixClientTransport::connect(path.clone())),
+        Endpoint::Tls(addr) => {
+            let ca = options.ca.ok_or("a tls:// upstream requires --ca")?;
+            let roots = load_certs(ca).map_err(|e| e.to_string())?;
+            let server_name = match options.server_name {
+                Some(name) => name,
+                None => addr.rsplit_once(':').map_or(addr.as_str(), |(host, _)| host).to_string(),
+            };
+            Relay::new(downstream, move || {
+                let (addr, server_name, roots) = (addr.clone(), server_name.clone(), roots.clone());
+                async move { TlsClientTransport::connect(addr, &server_name, roots).await }
+            })
+        }
+        Endpoint::Ws(url) => Relay::new(downstream, move || {
+            let url = url.clone();
+            async move { WsClientTransport::connect(&url).await }
+        }),
+    };
+    // Ctrl-C 时关闭所有会话后正常返回
+    let shutdown = relay.shutdown_handle();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            shutdown.shutdown();
+        }
+    });
+    relay.run().await.map_err(|e| format!("{:?}", e))
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use bytes::Bytes;
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+use crate::protocol::{now_millis, MsgType, Packet, PacketHeader};
+use crate::rpc::{CallContext, RpcError};
+use crate::transport::Transport;
+
+type PendingCalls = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Bytes, RpcError>>>>>;
+
+pub struct RpcClient {
+    session_id: u64,
+    outgoing: mpsc::Sender<Packet>,
+    pending: PendingCalls,
+    next_request_id: AtomicU64,
+}
+
+impl RpcClient {
+    pub fn new<T: Transport + Send + Sync + 'static>(mut transport: T) -> Self {
+        let (outgoing, mut outgoing_receiver) = mpsc::channel::<Packet>(100);
+        let pending: PendingCalls = Arc::new(Mutex::new(HashMap::new()));
+        let routing = Arc::clone(&pending);
+
+        // RpcClient 被丢弃后发送端关闭，主任务随之退出
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    packet = outgoing_receiver.recv() => match packet {
+                        // 客户端传输只有一条连接，目标 UUID 不起作用
+                        Some(packet) => if let Err(e) = transport.send(Uuid::nil(), packet).await {
+                            log::error!("RPC client send error: {:?}", e);
+                            break;
+                        },
+                        None => break,
+                    },
+                    incoming = transport.receive() => match incoming {
+                        Some((_, packet)) => Self::route(&routing, packet),
+                        None => break,
+                    },
+                }
+            }
+            // 连接断开，通知所有等待中的调用
+            for (_, sender) in routing.lock().unwrap().drain() {
+                let _ = sender.send(Err(RpcError::Closed));
+            }
+            let _ = transport.close().await;
+        });
+
+        RpcClient {
+            session_id: 0,
+            outgoing,
+            pending,
+            next_request_id: AtomicU64::new(1),
+        }
+    }
+
+    pub async fn call(&self, payload: Vec<u8>) -> Result<Bytes, RpcError> {
+        self.call_inner(payload, 0).await
+    }
+
+    // 超时后调用 future 被丢弃，会自动发送 Cancel
+    pub async fn call_timeout(&self, payload: Vec<u8>, timeout: Duration) -> Result<Bytes, RpcError> {
+        let deadline = now_millis() + timeout.as_millis() as u64;
+        self.call_with_deadline(payload, deadline).await
+    }
+
+    // 截止时间随 Call 一起发送，本地也在截止时放弃等待
+    pub async fn call_with_deadline(&self, payload: Vec<u8>, deadline: u64) -> Result<Bytes, RpcError> {
+        let remaining = deadline.saturating_sub(now_millis());
+        if remaining == 0 {
+            return Err(RpcError::Timeout);
+        }
+        tokio::time::timeout(Duration::from_millis(remaining), self.call_inner(payload, deadline))
+            .await
+            .unwrap_or(Err(RpcError::Timeout))
+    }
+
+    // 在处理器内发起下游调用，沿用上游调用剩余的时间预算
+    pub async fn call_in(&self, context: &CallContext, payload: Vec<u8>) -> Result<Bytes, RpcError> {
+        match context.deadline() {
+            Some(deadline) => self.call_with_deadline(payload, deadline).await,
+            None => self.call(payload).await,
+        }
+    }
+
+    async fn call_inner(&self, payload: Vec<u8>, deadline: u64) -> Result<Bytes, RpcError> {
+        let request_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(request_id, sender);
+
+        // 调用 future 在完成前被丢弃时，由守卫清理等待项
+        // 只有 Call 已交给传输后才通知服务端取消
+        let mut guard = CancelGuard {
+            client: self,
+            request_id,
+            sent: false,
+            armed: true,
+        };
+        let mut header = PacketHeader::for_request(&payload, self.session_id, MsgType::Call, request_id);
+        header.deadline = deadline;
+        self.outgoing
+            .send(Packet::new(header, payload))
+            .await
+            .map_err(|_| RpcError::Closed)?;
+        guard.sent = true;
+
+        let result = receiver.await.unwrap_or(Err(RpcError::Closed));
+        guard.armed = false;
+        result
+    }
+
+    fn route(pending: &PendingCalls, packet: Packet) {
+        let request_id = packet.header.request_id;
+        let result = match packet.header.msg_type {
+            MsgType::Reply => Ok(packet.payload),
+            MsgType::DeadlineExceeded => Err(RpcError::Timeout),
+            MsgType::Error => Err(RpcError::Remote(packet.payload)),
+            msg_type => {
+                log::warn!("Unexpected {:?} packet for request {}", msg_type, request_id);
+                return;
+            }
+        };
+        match pending.lock().unwrap().remove(&request_id) {
+            Some(sender) => {
+                let _ = sender.send(result);
+            }
+            None => log::debug!("Reply for unknown or cancelled request {}", request_id),
+        }
+    }
+}
+
+struct CancelGuard<'a> {
+    client: &'a RpcClient,
+    request_id: u64,
+    // Call 是否已交给传输，未发出的调用无需取消
+    sent: bool,
+    armed: bool,
+}
+
+impl Drop for CancelGuard<'_> {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        self.client.pending.lock().unwrap().remove(&self.request_id);
+        if !self.sent {
+            return;
+        }
+        let header = PacketHeader::for_request(&[], self.client.session_id, MsgType::Cancel, self.request_id);
+        if self.client.outgoing.try_send(Packet::new(header, Vec::new())).is_err() {
+            log::warn!("Failed to send cancel for request {}", self.request_id);
+        }
+    }
+}
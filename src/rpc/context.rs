@@ -0,0 +1,60 @@
+use std::time::Duration;
+use tokio::sync::watch;
+use uuid::Uuid;
+use crate::protocol::now_millis;
+
+// 处理器执行期间可见的调用上下文
+#[derive(Clone)]
+pub struct CallContext {
+    uuid: Uuid,
+    request_id: u64,
+    deadline: u64,
+    cancel_receiver: watch::Receiver<bool>,
+}
+
+impl CallContext {
+    pub(crate) fn new(uuid: Uuid, request_id: u64, deadline: u64) -> (Self, watch::Sender<bool>) {
+        let (cancel_sender, cancel_receiver) = watch::channel(false);
+        let context = CallContext {
+            uuid,
+            request_id,
+            deadline,
+            cancel_receiver,
+        };
+        (context, cancel_sender)
+    }
+
+    // 发起调用的连接
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn request_id(&self) -> u64 {
+        self.request_id
+    }
+
+    // 调用方给出的截止时间（毫秒时间戳）
+    pub fn deadline(&self) -> Option<u64> {
+        (self.deadline != 0).then_some(self.deadline)
+    }
+
+    // 距截止时间的剩余预算，已过期时为 0
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline()
+            .map(|deadline| Duration::from_millis(deadline.saturating_sub(now_millis())))
+    }
+
+    // 客户端是否已取消本次调用
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancel_receiver.borrow()
+    }
+
+    // 等待取消信号，可与处理逻辑一起放进 select!
+    pub async fn cancelled(&self) {
+        let mut receiver = self.cancel_receiver.clone();
+        // 发送端被丢弃说明调用已结束，此后不会再有取消信号
+        if receiver.wait_for(|cancelled| *cancelled).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
@@ -0,0 +1,24 @@
+mod context;
+mod server;
+mod client;
+mod pool;
+
+pub use context::CallContext;
+pub use server::{Handler, HandlerResult, RpcServer};
+pub use client::RpcClient;
+pub use pool::{Balance, PoolConfig, RpcPool};
+
+use bytes::Bytes;
+use crate::transport::TransportError;
+
+// RPC 调用错误类型
+#[derive(Debug)]
+pub enum RpcError {
+    Transport(TransportError),
+    // 对端返回的错误内容
+    Remote(Bytes),
+    Timeout,
+    Closed,
+    // 连接池中没有可用的端点
+    Unavailable,
+}
@@ -0,0 +1,356 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use bytes::Bytes;
+use futures_util::future::join_all;
+use tokio::sync::watch;
+use xxhash_rust::xxh32::xxh32;
+use crate::discovery::Discovery;
+use crate::rpc::{RpcClient, RpcError};
+use crate::transport::{TcpClientTransport, Transport, TransportError};
+
+// 每个端点在哈希环上的虚拟节点数，节点越多分布越均匀
+const VIRTUAL_NODES: u32 = 64;
+
+type ConnectFuture = Pin<Box<dyn Future<Output = Result<RpcClient, TransportError>> + Send>>;
+type Connector = Arc<dyn Fn(String) -> ConnectFuture + Send + Sync>;
+
+// 选择端点的策略
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Balance {
+    RoundRobin,
+    // 选进行中调用最少的端点
+    LeastOutstanding,
+    // 按 call_with_key 给出的键选端点，端点增减时只有少量键改变去向
+    ConsistentHash,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PoolConfig {
+    pub balance: Balance,
+    // 连续超时达到该次数后摘除端点，连接断开时立即摘除
+    pub eject_after: u32,
+    // 摘除后等待多久重新尝试连接
+    pub probe_interval: Duration,
+    pub connect_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            balance: Balance::RoundRobin,
+            eject_after: 3,
+            probe_interval: Duration::from_secs(5),
+            connect_timeout: Duration::from_secs(3),
+        }
+    }
+}
+
+struct Endpoint {
+    addr: String,
+    // 被摘除或尚未连上时为 None
+    client: Option<Arc<RpcClient>>,
+    outstanding: Arc<AtomicUsize>,
+    failures: u32,
+    // 下一次重新连接的时间，连接期间推迟到连接超时之后
+    retry_at: Instant,
+}
+
+impl Endpoint {
+    fn new(addr: String) -> Self {
+        Endpoint {
+            addr,
+            client: None,
+            outstanding: Arc::new(AtomicUsize::new(0)),
+            failures: 0,
+            retry_at: Instant::now(),
+        }
+    }
+}
+
+struct PoolState {
+    endpoints: Vec<Endpoint>,
+    // 按哈希值排序的 (哈希, 端点下标)
+    ring: Vec<(u32, usize)>,
+}
+
+impl PoolState {
+    fn rebuild_ring(&mut self) {
+        self.ring = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .flat_map(|(index, endpoint)| {
+                (0..VIRTUAL_NODES).map(move |node| (xxh32(endpoint.addr.as_bytes(), node), index))
+            })
+            .collect();
+        self.ring.sort_unstable();
+    }
+}
+
+// 连接多个服务端并在其间分配调用的客户端
+// 后台任务定期重连被摘除的端点，连接成功即恢复使用
+pub struct RpcPool {
+    state: Arc<Mutex<PoolState>>,
+    connector: Connector,
+    config: PoolConfig,
+    next: AtomicUsize,
+}
+
+impl RpcPool {
+    // 通过 TCP 连接各端点
+    pub async fn new(endpoints: Vec<String>, config: PoolConfig) -> Self {
+        Self::with_connector(endpoints, config, TcpClientTransport::connect).await
+    }
+
+    // connector 根据地址建立任意一种客户端传输
+    pub async fn with_connector<C, F, T>(endpoints: Vec<String>, config: PoolConfig, connector: C) -> Self
+    where
+        C: Fn(String) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<T, TransportError>> + Send + 'static,
+        T: Transport + Send + Sync + 'static,
+    {
+        let connector: Connector = Arc::new(move |addr| {
+            let connecting = connector(addr);
+            Box::pin(async move { connecting.await.map(RpcClient::new) })
+        });
+        let mut state = PoolState {
+            endpoints: endpoints.into_iter().map(Endpoint::new).collect(),
+            ring: Vec::new(),
+        };
+        state.rebuild_ring();
+        let state = Arc::new(Mutex::new(state));
+
+        // 首次连接与重连走同一条路径，连不上的端点从一开始就处于摘除状态
+        probe(&state, &connector, &config).await;
+        tokio::spawn(probe_loop(Arc::downgrade(&state), Arc::clone(&connector), config));
+
+        RpcPool {
+            state,
+            connector,
+            config,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    // 通过 TCP 连接服务发现给出的端点，并随发现结果自动增减
+    pub async fn discover(discovery: &dyn Discovery, service: &str, config: PoolConfig) -> Self {
+        let endpoints = discovery.subscribe(service);
+        let pool = Self::new(endpoints.borrow().clone(), config).await;
+        pool.follow(endpoints);
+        pool
+    }
+
+    // 端点列表变化时更新连接池，仍在列表中的端点保留已有连接
+    // 发送端关闭或连接池被丢弃后停止
+    pub fn follow(&self, mut endpoints: watch::Receiver<Vec<String>>) {
+        let state = Arc::downgrade(&self.state);
+        let connector = Arc::clone(&self.connector);
+        let config = self.config;
+        tokio::spawn(async move {
+            while endpoints.changed().await.is_ok() {
+                let Some(state) = state.upgrade() else {
+                    break;
+                };
+                let addrs = endpoints.borrow_and_update().clone();
+                log::info!("Updating pool endpoints to {:?}", addrs);
+                set_endpoints(&state, addrs);
+                // 新增的端点立即连接，不等下一轮重连
+                probe(&state, &connector, &config).await;
+            }
+        });
+    }
+
+    // 替换端点列表，新增的端点立即尝试连接
+    pub async fn set_endpoints(&self, endpoints: Vec<String>) {
+        set_endpoints(&self.state, endpoints);
+        probe(&self.state, &self.connector, &self.config).await;
+    }
+
+    pub async fn call(&self, payload: Vec<u8>) -> Result<Bytes, RpcError> {
+        self.call_inner(None, payload, None).await
+    }
+
+    pub async fn call_timeout(&self, payload: Vec<u8>, timeout: Duration) -> Result<Bytes, RpcError> {
+        self.call_inner(None, payload, Some(timeout)).await
+    }
+
+    // 一致性哈希策略下同一个键总是落到同一个健康端点，其它策略忽略键
+    pub async fn call_with_key(&self, key: &[u8], payload: Vec<u8>) -> Result<Bytes, RpcError> {
+        self.call_inner(Some(key), payload, None).await
+    }
+
+    // 当前可用的端点地址
+    pub fn healthy_endpoints(&self) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.client.is_some())
+            .map(|endpoint| endpoint.addr.clone())
+            .collect()
+    }
+
+    async fn call_inner(&self, key: Option<&[u8]>, payload: Vec<u8>, timeout: Option<Duration>) -> Result<Bytes, RpcError> {
+        let (client, outstanding) = self.pick(key).ok_or(RpcError::Unavailable)?;
+        // 调用方丢弃 future 时由守卫减回计数
+        let guard = OutstandingGuard::new(outstanding);
+        let result = match timeout {
+            Some(timeout) => client.call_timeout(payload, timeout).await,
+            None => client.call(payload).await,
+        };
+        drop(guard);
+        self.record(&client, &result);
+        result
+    }
+
+    fn pick(&self, key: Option<&[u8]>) -> Option<(Arc<RpcClient>, Arc<AtomicUsize>)> {
+        let state = self.state.lock().unwrap();
+        let healthy: Vec<usize> = (0..state.endpoints.len())
+            .filter(|&index| state.endpoints[index].client.is_some())
+            .collect();
+        if healthy.is_empty() {
+            return None;
+        }
+        let index = match (self.config.balance, key) {
+            (Balance::ConsistentHash, Some(key)) => {
+                let hash = xxh32(key, 0);
+                let start = state.ring.partition_point(|&(point, _)| point < hash);
+                // 顺着环找到第一个健康端点
+                (0..state.ring.len())
+                    .map(|offset| state.ring[(start + offset) % state.ring.len()].1)
+                    .find(|&index| state.endpoints[index].client.is_some())?
+            }
+            (Balance::LeastOutstanding, _) => {
+                // 从轮询位置开始比较，进行中调用数相同时轮流选择
+                let start = self.next.fetch_add(1, Ordering::Relaxed);
+                (0..healthy.len())
+                    .map(|offset| healthy[(start + offset) % healthy.len()])
+                    .min_by_key(|&index| state.endpoints[index].outstanding.load(Ordering::Relaxed))?
+            }
+            // 一致性哈希未给出键时退化为轮询
+            _ => healthy[self.next.fetch_add(1, Ordering::Relaxed) % healthy.len()],
+        };
+        let endpoint = &state.endpoints[index];
+        Some((Arc::clone(endpoint.client.as_ref()?), Arc::clone(&endpoint.outstanding)))
+    }
+
+    // 连接断开立即摘除，连续超时达到阈值时摘除，其它结果说明端点正常
+    fn record(&self, client: &Arc<RpcClient>, result: &Result<Bytes, RpcError>) {
+        let mut state = self.state.lock().unwrap();
+        // 端点可能在调用期间已被摘除或重连，只处理仍是同一连接的情况
+        let Some(endpoint) = state
+            .endpoints
+            .iter_mut()
+            .find(|endpoint| endpoint.client.as_ref().is_some_and(|current| Arc::ptr_eq(current, client)))
+        else {
+            return;
+        };
+        let eject = match result {
+            Err(RpcError::Closed) | Err(RpcError::Transport(_)) => true,
+            Err(RpcError::Timeout) => {
+                endpoint.failures += 1;
+                endpoint.failures >= self.config.eject_after
+            }
+            _ => {
+                endpoint.failures = 0;
+                false
+            }
+        };
+        if eject {
+            log::warn!("Ejecting endpoint {} after {:?}", endpoint.addr, result.as_ref().err());
+            endpoint.client = None;
+            endpoint.failures = 0;
+            endpoint.retry_at = Instant::now() + self.config.probe_interval;
+        }
+    }
+}
+
+// 进行中调用计数，创建时加一，丢弃时减一
+struct OutstandingGuard(Arc<AtomicUsize>);
+
+impl OutstandingGuard {
+    fn new(outstanding: Arc<AtomicUsize>) -> Self {
+        outstanding.fetch_add(1, Ordering::Relaxed);
+        OutstandingGuard(outstanding)
+    }
+}
+
+impl Drop for OutstandingGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn set_endpoints(state: &Mutex<PoolState>, addrs: Vec<String>) {
+    let mut state = state.lock().unwrap();
+    let mut old = std::mem::take(&mut state.endpoints);
+    state.endpoints = addrs
+        .into_iter()
+        .map(|addr| match old.iter().position(|endpoint| endpoint.addr == addr) {
+            Some(index) => old.swap_remove(index),
+            None => Endpoint::new(addr),
+        })
+        .collect();
+    // 移除的端点随 old 一起丢弃，其连接在进行中的调用结束后关闭
+    state.rebuild_ring();
+}
+
+async fn probe_loop(state: Weak<Mutex<PoolState>>, connector: Connector, config: PoolConfig) {
+    loop {
+        tokio::time::sleep(config.probe_interval).await;
+        // 连接池被丢弃后退出
+        let Some(state) = state.upgrade() else {
+            break;
+        };
+        probe(&state, &connector, &config).await;
+    }
+}
+
+// 并发尝试连接所有已到重连时间的端点，不可达的地址不会拖慢其它端点
+async fn probe(state: &Mutex<PoolState>, connector: &Connector, config: &PoolConfig) {
+    let now = Instant::now();
+    // 在锁内把重连时间推迟到连接超时之后，同时进行的其它 probe 会跳过这些端点
+    // probe 中途被丢弃时，端点在超时后重新变为可连接
+    let due: Vec<String> = state
+        .lock()
+        .unwrap()
+        .endpoints
+        .iter_mut()
+        .filter(|endpoint| endpoint.client.is_none() && endpoint.retry_at <= now)
+        .map(|endpoint| {
+            endpoint.retry_at = now + config.connect_timeout;
+            endpoint.addr.clone()
+        })
+        .collect();
+
+    let attempts = due.into_iter().map(|addr| async move {
+        let result = tokio::time::timeout(config.connect_timeout, connector(addr.clone())).await;
+        (addr, result)
+    });
+    for (addr, result) in join_all(attempts).await {
+        let mut state = state.lock().unwrap();
+        let Some(endpoint) = state.endpoints.iter_mut().find(|endpoint| endpoint.addr == addr) else {
+            continue;
+        };
+        match result {
+            // 端点在连接期间被移除后又重新加入时，可能已由另一次 probe 连上
+            Ok(Ok(_)) if endpoint.client.is_some() => {}
+            Ok(Ok(client)) => {
+                log::info!("Endpoint {} is available", addr);
+                endpoint.client = Some(Arc::new(client));
+                endpoint.failures = 0;
+            }
+            Ok(Err(e)) => {
+                log::warn!("Failed to connect to endpoint {}: {:?}", addr, e);
+                endpoint.retry_at = Instant::now() + config.probe_interval;
+            }
+            Err(_) => {
+                log::warn!("Connecting to endpoint {} timed out", addr);
+                endpoint.retry_at = Instant::now() + config.probe_interval;
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use bytes::Bytes;
+use tokio::sync::{mpsc, watch};
+use uuid::Uuid;
+use crate::logger::LogContext;
+use crate::protocol::{now_millis, MsgType, Packet, PacketHeader};
+use crate::rpc::CallContext;
+use crate::transport::{Transport, TransportError};
+
+// 处理器返回 Ok 时回复 Reply，返回 Err 时回复 Error
+pub type HandlerResult = Result<Vec<u8>, Vec<u8>>;
+pub type Handler = Arc<
+    dyn Fn(CallContext, Bytes) -> Pin<Box<dyn Future<Output = HandlerResult> + Send>> + Send + Sync,
+>;
+
+type RunningCalls = Arc<Mutex<HashMap<(Uuid, u64), watch::Sender<bool>>>>;
+
+pub struct RpcServer<T: Transport> {
+    transport: T,
+    handler: Handler,
+    // 正在执行的调用，用于投递取消信号
+    running: RunningCalls,
+}
+
+impl<T: Transport + Send> RpcServer<T> {
+    pub fn new(transport: T, handler: Handler) -> Self {
+        RpcServer {
+            transport,
+            handler,
+            running: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn with_handler<F, Fut>(transport: T, handler: F) -> Self
+    where
+        F: Fn(CallContext, Bytes) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HandlerResult> + Send + 'static,
+    {
+        Self::new(transport, Arc::new(move |context, payload| Box::pin(handler(context, payload))))
+    }
+
+    pub async fn serve(mut self) -> Result<(), TransportError> {
+        log::info!("RPC server started");
+        // 处理器任务通过该通道把回复交还给主循环发送
+        let (reply_sender, mut reply_receiver) = mpsc::channel::<(Uuid, Packet)>(100);
+        loop {
+            tokio::select! {
+                incoming = self.transport.receive() => match incoming {
+                    Some((uuid, packet)) => self.dispatch(uuid, packet, &reply_sender),
+                    None => break,
+                },
+                Some((uuid, packet)) = reply_receiver.recv() => {
+                    if let Err(e) = self.transport.send(uuid, packet).await {
+                        log::warn!("Failed to send reply to {}: {:?}", uuid, e);
+                    }
+                }
+            }
+        }
+        log::warn!("RPC server stopped: transport closed");
+        self.transport.close().await
+    }
+
+    fn dispatch(&self, uuid: Uuid, packet: Packet, reply_sender: &mpsc::Sender<(Uuid, Packet)>) {
+        let request_id = packet.header.request_id;
+        match packet.header.msg_type {
+            MsgType::Call => {
+                let session_id = packet.header.session_id;
+                let deadline = packet.header.deadline;
+                let reply_sender = reply_sender.clone();
+                // 已过期的调用不再分发给处理器
+                if deadline != 0 && now_millis() >= deadline {
+                    log::warn!("Call {} from {} expired before dispatch", request_id, uuid);
+                    tokio::spawn(async move {
+                        let header = PacketHeader::for_request(&[], session_id, MsgType::DeadlineExceeded, request_id);
+                        let _ = reply_sender.send((uuid, Packet::new(header, Vec::new()))).await;
+                    });
+                    return;
+                }
+
+                let (context, cancel_sender) = CallContext::new(uuid, request_id, deadline);
+                self.running.lock().unwrap().insert((uuid, request_id), cancel_sender);
+
+                // 处理器内的日志都带上连接、会话与请求标识
+                let log_context = LogContext {
+                    uuid: Some(uuid),
+                    session_id: Some(session_id).filter(|&id| id != 0),
+                    request_id: Some(request_id),
+                    peer_addr: None,
+                };
+                let call = (self.handler)(context.clone(), packet.payload);
+                let running = Arc::clone(&self.running);
+                tokio::spawn(log_context.scope(async move {
+                    let result = call.await;
+                    running.lock().unwrap().remove(&(uuid, request_id));
+                    // 客户端已放弃该调用，不再回复
+                    if context.is_cancelled() {
+                        log::info!("Call finished after cancellation");
+                        return;
+                    }
+                    let _ = reply_sender.send((uuid, Self::reply(session_id, request_id, result))).await;
+                }));
+            }
+            MsgType::Cancel => {
+                match self.running.lock().unwrap().get(&(uuid, request_id)) {
+                    Some(cancel_sender) => {
+                        log::info!("Call {} from {} cancelled by client", request_id, uuid);
+                        let _ = cancel_sender.send(true);
+                    }
+                    None => log::debug!("Cancel for unknown call {} from {}", request_id, uuid),
+                }
+            }
+            msg_type => {
+                log::warn!("Unexpected {:?} packet from {}", msg_type, uuid);
+            }
+        }
+    }
+
+    fn reply(session_id: u64, request_id: u64, result: HandlerResult) -> Packet {
+        let (msg_type, payload) = match result {
+            Ok(payload) => (MsgType::Reply, payload),
+            Err(payload) => (MsgType::Error, payload),
+        };
+        let header = PacketHeader::for_request(&payload, session_id, msg_type, request_id);
+        Packet::new(header, payload)
+    }
+}
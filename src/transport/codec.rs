@@ -0,0 +1,52 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use bytes::{Buf, BytesMut};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use crate::protocol::{Packet, HEADER_SIZE};
+use crate::transport::TransportError;
+
+// 默认允许的最大 payload 长度
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+static MAX_FRAME_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_FRAME_SIZE);
+
+// 设置流式传输（TCP、unix、TLS、stdio、QUIC）读取时允许的最大 payload 长度，对之后读到的包生效
+// 包头声明的长度超出时不分配缓冲区，直接以 MsgError 结束该连接
+pub fn set_max_frame_size(size: usize) {
+    MAX_FRAME_SIZE.store(size, Ordering::Relaxed);
+}
+
+pub fn max_frame_size() -> usize {
+    MAX_FRAME_SIZE.load(Ordering::Relaxed)
+}
+
+// 从流中读取一个完整的包，payload 切片自读缓冲区，不再拷贝
+pub(crate) async fn read_packet<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buf: &mut BytesMut,
+) -> Result<Packet, TransportError> {
+    buf.clear();
+    buf.resize(HEADER_SIZE, 0);
+    reader.read_exact(&mut buf[..]).await.map_err(|_| TransportError::ReceiveError)?;
+
+    let payload_len = u32::from_le_bytes(buf[16..20].try_into().unwrap()) as usize;
+    if payload_len > max_frame_size() {
+        log::warn!("Rejecting frame with payload of {} bytes", payload_len);
+        return Err(TransportError::MsgError);
+    }
+    buf.resize(HEADER_SIZE + payload_len, 0);
+    reader.read_exact(&mut buf[HEADER_SIZE..]).await.map_err(|_| TransportError::ReceiveError)?;
+
+    Packet::decode(buf.split().freeze()).map_err(|_| TransportError::MsgError)
+}
+
+// 包头编码进可复用的写缓冲区，与共享的 payload 一起写出
+pub(crate) async fn write_packet<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    packet: &Packet,
+    buf: &mut BytesMut,
+) -> std::io::Result<()> {
+    buf.clear();
+    packet.encode_header(buf);
+    let mut frame = Buf::chain(&buf[..], &packet.payload[..]);
+    writer.write_all_buf(&mut frame).await
+}
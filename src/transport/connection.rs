@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use bytes::BytesMut;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+use crate::logger::LogContext;
+use crate::protocol::{Packet, SharedReplayGuard, HEADER_SIZE};
+use crate::transport::codec;
+
+// 连接结束通知，可以在服务端 run 之后订阅，只推送订阅之后结束的连接
+#[derive(Clone, Default)]
+pub(crate) struct ClosedNotifier(Arc<std::sync::Mutex<Option<mpsc::UnboundedSender<Uuid>>>>);
+
+impl ClosedNotifier {
+    // 重复订阅时替换之前的接收端
+    pub(crate) fn subscribe(&self) -> mpsc::UnboundedReceiver<Uuid> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        *self.0.lock().unwrap() = Some(sender);
+        receiver
+    }
+
+    pub(crate) fn notify(&self, uuid: Uuid) {
+        if let Some(sender) = self.0.lock().unwrap().as_ref() {
+            let _ = sender.send(uuid);
+        }
+    }
+}
+
+// 服务端连接表中一条连接的写入端
+// 被移除（disconnect 或服务端关闭）时写入任务与读取循环一起结束，之后收到的包不再转发
+pub(crate) struct ConnectionSender {
+    sender: mpsc::Sender<Packet>,
+    // 从不发送，随连接表项一起丢弃时通知读取循环
+    _stop: oneshot::Sender<()>,
+}
+
+impl ConnectionSender {
+    pub(crate) async fn send(&self, packet: Packet) -> Result<(), mpsc::error::SendError<Packet>> {
+        self.sender.send(packet).await
+    }
+}
+
+// 连接任务一端：待写出的包与停止信号
+pub(crate) struct ConnectionReceiver {
+    pub(crate) packets: mpsc::Receiver<Packet>,
+    pub(crate) stop: oneshot::Receiver<()>,
+}
+
+pub(crate) fn channel() -> (ConnectionSender, ConnectionReceiver) {
+    let (sender, packets) = mpsc::channel(100);
+    let (stop_sender, stop) = oneshot::channel();
+    (ConnectionSender { sender, _stop: stop_sender }, ConnectionReceiver { packets, stop })
+}
+
+// 服务端的一条连接：读到的包交给 output_sender，write_receiver 中的包写回对端
+// 连接结束时从 connections 中移除并向 closed 推送 uuid，各服务端传输共用
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn serve_connection<S, V>(
+    stream: S,
+    uuid: Uuid,
+    context: LogContext,
+    output_sender: mpsc::Sender<(Uuid, Packet)>,
+    write_receiver: ConnectionReceiver,
+    connections: Arc<Mutex<HashMap<Uuid, V>>>,
+    replay_guard: Option<SharedReplayGuard>,
+    closed: ClosedNotifier,
+) -> JoinHandle<()>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+    V: Send + 'static,
+{
+    // 连接内的日志都带上 uuid 与对端地址
+    tokio::spawn(context.clone().scope(async move {
+        log::info!("Connection handler started");
+
+        let (mut read_half, mut write_half) = tokio::io::split(stream);
+        let ConnectionReceiver { packets: mut write_receiver, mut stop } = write_receiver;
+
+        // 写入任务
+        let mut write_handle = tokio::spawn(context.scope(async move {
+            let mut sequence = 0u64;
+            let mut write_buf = BytesMut::with_capacity(HEADER_SIZE);
+            while let Some(mut packet) = write_receiver.recv().await {
+                // 为每个发出的包分配递增序号
+                sequence += 1;
+                packet.header.sequence = sequence;
+                if let Err(e) = codec::write_packet(&mut write_half, &packet, &mut write_buf).await {
+                    log::error!("Write error: {}", e);
+                    break;
+                }
+            }
+            // 发送端被移除（disconnect 或服务端关闭）时通知对端连接结束
+            let _ = write_half.shutdown().await;
+            log::info!("Write task ended");
+        }));
+
+        // 读取任务
+        let mut read_buf = BytesMut::new();
+        let removed = loop {
+            let result = tokio::select! {
+                biased;
+                _ = &mut stop => break true,
+                result = codec::read_packet(&mut read_half, &mut read_buf) => result,
+            };
+            match result {
+                Ok(packet) => {
+                    if let Some(guard) = replay_guard.as_ref()
+                        && let Err(e) = guard.lock().unwrap().check(uuid, &packet.header)
+                    {
+                        log::warn!("Dropping packet: {:?}", e);
+                        continue;
+                    }
+                    if output_sender.send((uuid, packet)).await.is_err() {
+                        log::warn!("Output receiver closed, stopping read");
+                        break false;
+                    }
+                }
+                Err(e) => {
+                    log::error!("Read error: {:?}", e);
+                    break false;
+                }
+            }
+        };
+
+        // 对端断开或读取出错时移除，之后向该连接发送返回 ConnectionNotFound
+        connections.lock().await.remove(&uuid);
+        if let Some(guard) = replay_guard.as_ref() {
+            guard.lock().unwrap().remove(uuid);
+        }
+        finish_write(removed, &mut write_handle).await;
+        closed.notify(uuid);
+        log::info!("Connection handler ended");
+    }))
+}
+
+// 连接被移除时写入任务会写完排队的包并关闭写方向，等它结束；否则直接中止
+pub(crate) async fn finish_write(removed: bool, write_handle: &mut JoinHandle<()>) {
+    if removed {
+        log::info!("Connection removed, stopping read");
+        let _ = write_handle.await;
+    } else {
+        write_handle.abort();
+    }
+}
+
+// 客户端的唯一连接，读写任务与服务端相同但不做重放检查
+pub(crate) fn run_client<S>(
+    stream: S,
+    uuid: Uuid,
+    context: LogContext,
+    output_sender: mpsc::Sender<(Uuid, Packet)>,
+    mut packet_receiver: mpsc::Receiver<Packet>,
+) -> JoinHandle<()>
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    tokio::spawn(context.scope(async move{
+        let (mut read_half, mut write_half) = tokio::io::split(stream);
+        // 读写在同一个任务中，close 中止该任务时两半一起释放，连接随之关闭；任一方向结束时另一方向也停止
+        let write = async {
+            let mut sequence = 0u64;
+            let mut write_buf = BytesMut::with_capacity(HEADER_SIZE);
+            while let Some(mut packet) = packet_receiver.recv().await {
+                // 为每个发出的包分配递增序号
+                sequence += 1;
+                packet.header.sequence = sequence;
+                if let Err(e) = codec::write_packet(&mut write_half, &packet, &mut write_buf).await {
+                    log::error!("Write error: {}", e);
+                    break;
+                }
+            }
+        };
+
+        let read = async {
+            let mut read_buf = BytesMut::new();
+            loop {
+                match codec::read_packet(&mut read_half, &mut read_buf).await {
+                    Ok(packet) => {
+                        if output_sender.send((uuid, packet)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("Read error: {:?}", e);
+                        break;
+                    }
+                }
+            }
+        };
+
+        tokio::select! {
+            _ = write => {}
+            _ = read => {}
+        }
+    }))
+}
@@ -1,13 +1,53 @@
 use crate::protocol::Packet;
 use crate::transport::TransportError;
-
-type PacketMessage = (Result<Packet,TransportError>, u8);
+use tokio::sync::mpsc;
 
 struct Agent {
     id: u16,
-
+    sender: mpsc::Sender<Packet>,
 }
 
-struct Hub{
+// 在多个 Agent 之间转发包，转发与广播只克隆 payload 的引用
+pub struct Hub{
     agents: Vec<Agent>,
-}
\ No newline at end of file
+}
+
+impl Hub {
+    pub fn new() -> Self {
+        Hub { agents: Vec::new() }
+    }
+
+    // 注册 Agent，返回其接收端；重复注册会替换旧的接收端
+    pub fn register(&mut self, id: u16) -> mpsc::Receiver<Packet> {
+        let (sender, receiver) = mpsc::channel(100);
+        self.agents.retain(|agent| agent.id != id);
+        self.agents.push(Agent { id, sender });
+        receiver
+    }
+
+    pub fn unregister(&mut self, id: u16) {
+        self.agents.retain(|agent| agent.id != id);
+    }
+
+    pub async fn forward(&self, id: u16, packet: Packet) -> Result<(), TransportError> {
+        let agent = self.agents
+            .iter()
+            .find(|agent| agent.id == id)
+            .ok_or(TransportError::ConnectionNotFound)?;
+        agent.sender.send(packet).await.map_err(|_| TransportError::SendError)
+    }
+
+    pub async fn broadcast(&self, packet: Packet) {
+        for agent in &self.agents {
+            if agent.sender.send(packet.clone()).await.is_err() {
+                log::warn!("Failed to forward packet to agent {}", agent.id);
+            }
+        }
+    }
+}
+
+impl Default for Hub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,92 @@
+use std::io;
+use crate::logger::LogContext;
+use crate::protocol::Packet;
+use crate::transport::memory_server::{MemoryConnector, PendingConnection};
+use crate::transport::{Transport, TransportError};
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+pub struct MemoryClientTransport {
+    uuid: Uuid,
+    input_sender: mpsc::Sender<Packet>,
+    input_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+    write_handle: Option<JoinHandle<()>>,
+}
+
+impl MemoryClientTransport {
+    // 服务端已关闭时返回 ConnectionRefused，与连接已关闭的 TCP 端口一致
+    pub async fn connect(connector: &MemoryConnector) -> Result<Self, TransportError> {
+        let (to_server, incoming) = mpsc::channel::<Packet>(100);
+        let (outgoing, mut from_server) = mpsc::channel::<Packet>(100);
+        connector
+            .accept_sender
+            .send(PendingConnection { incoming, outgoing })
+            .await
+            .map_err(|_| TransportError::Io(io::ErrorKind::ConnectionRefused.into()))?;
+
+        // 客户端只有一条连接，用固定的 UUID 标识
+        let uuid = Uuid::new_v4();
+        let (input_sender, mut packet_receiver) = mpsc::channel::<Packet>(100);
+        let (output_sender, input_receiver) = mpsc::channel(100);
+        let context = LogContext::for_connection(uuid, None);
+
+        let write_handle = tokio::spawn(context.clone().scope(async move {
+            let mut sequence = 0u64;
+            while let Some(mut packet) = packet_receiver.recv().await {
+                // 为每个发出的包分配递增序号
+                sequence += 1;
+                packet.header.sequence = sequence;
+                if to_server.send(packet).await.is_err() {
+                    break;
+                }
+            }
+        }));
+        let main_handle = tokio::spawn(context.scope(async move {
+            while let Some(packet) = from_server.recv().await {
+                if output_sender.send((uuid, packet)).await.is_err() {
+                    break;
+                }
+            }
+        }));
+
+        Ok(MemoryClientTransport {
+            uuid,
+            input_sender,
+            input_receiver,
+            main_handle: Some(main_handle),
+            write_handle: Some(write_handle),
+        })
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryClientTransport {
+    async fn send(&self, _uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        self.input_sender.send(packet)
+            .await
+            .map_err(|_| TransportError::SendError)?;
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.input_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        // 读写任务都结束，服务端随即移除该连接
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        if let Some(handle) = self.write_handle.take() {
+            handle.abort();
+        }
+        Ok(())
+    }
+}
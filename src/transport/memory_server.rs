@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::logger::LogContext;
+use crate::protocol::{Packet, ReplayConfig, ReplayGuard, SharedReplayGuard};
+use crate::transport::connection::{self, ClosedNotifier, ConnectionReceiver, ConnectionSender};
+use crate::transport::{Transport, TransportError};
+use async_trait::async_trait;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+type Connections = Arc<Mutex<HashMap<Uuid, ConnectionSender>>>;
+
+// 客户端发起连接时交给服务端的一对通道
+pub(super) struct PendingConnection {
+    // 客户端发来的包
+    pub(super) incoming: mpsc::Receiver<Packet>,
+    // 发往客户端的包
+    pub(super) outgoing: mpsc::Sender<Packet>,
+}
+
+// 连接到某个内存服务端的句柄，相当于监听地址，可复制给任意多个客户端
+#[derive(Clone)]
+pub struct MemoryConnector {
+    pub(super) accept_sender: mpsc::Sender<PendingConnection>,
+}
+
+// 进程内的服务端传输，包经通道直接传递，不经过编解码
+// UUID 分配、序号与关闭行为与 TcpServerTransport 一致
+pub struct MemoryServerTransport {
+    accept_sender: mpsc::Sender<PendingConnection>,
+    accept_receiver: Option<mpsc::Receiver<PendingConnection>>,
+    connections: Connections,
+    output_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+    output_sender: mpsc::Sender<(Uuid, Packet)>,
+    replay_guard: Option<SharedReplayGuard>,
+    closed: ClosedNotifier,
+}
+
+impl MemoryServerTransport {
+    pub fn new() -> Self {
+        // 与 TCP 的监听队列类似，run 之前发起的连接会排队等待
+        let (accept_sender, accept_receiver) = mpsc::channel(100);
+        let (output_sender, output_receiver) = mpsc::channel(100);
+        MemoryServerTransport {
+            accept_sender,
+            accept_receiver: Some(accept_receiver),
+            connections: Arc::new(Mutex::new(HashMap::new())),
+            output_receiver,
+            main_handle: None,
+            output_sender,
+            replay_guard: None,
+            closed: ClosedNotifier::default(),
+        }
+    }
+
+    pub fn connector(&self) -> MemoryConnector {
+        MemoryConnector { accept_sender: self.accept_sender.clone() }
+    }
+
+    // 开启过期与重放检查，需在 run 之前调用
+    pub fn set_replay_protection(&mut self, config: ReplayConfig) {
+        self.replay_guard = Some(ReplayGuard::shared(config));
+    }
+
+    // 向所有连接发送同一个包，各连接共享 payload 缓冲区
+    pub async fn broadcast(&self, packet: Packet) -> Result<(), TransportError> {
+        let connections = self.connections.lock().await;
+        for (uuid, sender) in connections.iter() {
+            if sender.send(packet.clone()).await.is_err() {
+                log::warn!("Failed to broadcast packet to UUID {}", uuid);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn run(&mut self) {
+        let Some(mut accept_receiver) = self.accept_receiver.take() else {
+            log::warn!("MemoryServerTransport is already running");
+            return;
+        };
+        let replay_guard = self.replay_guard.clone();
+        let closed = self.closed.clone();
+        let connections = Arc::clone(&self.connections);
+        let output_sender = self.output_sender.clone();
+
+        self.main_handle = Some(tokio::spawn(async move {
+            log::info!("Memory server main loop started");
+            while let Some(pending) = accept_receiver.recv().await {
+                let uuid = Uuid::new_v4();
+                log::info!("New in-memory connection accepted - assigned UUID {}", uuid);
+                let (write_sender, write_receiver) = connection::channel();
+                connections.lock().await.insert(uuid, write_sender);
+                Self::serve_connection(
+                    pending,
+                    uuid,
+                    output_sender.clone(),
+                    write_receiver,
+                    Arc::clone(&connections),
+                    replay_guard.clone(),
+                    closed.clone(),
+                );
+            }
+            log::warn!("Memory server main loop exited");
+        }));
+    }
+
+    // 与 connection::serve_connection 相同，只是收发的是通道而不是字节流
+    fn serve_connection(
+        pending: PendingConnection,
+        uuid: Uuid,
+        output_sender: mpsc::Sender<(Uuid, Packet)>,
+        write_receiver: ConnectionReceiver,
+        connections: Connections,
+        replay_guard: Option<SharedReplayGuard>,
+        closed: ClosedNotifier,
+    ) {
+        let PendingConnection { mut incoming, outgoing } = pending;
+        let ConnectionReceiver { packets: mut write_receiver, mut stop } = write_receiver;
+        let context = LogContext::for_connection(uuid, None);
+        tokio::spawn(context.clone().scope(async move {
+            log::info!("Connection handler started");
+
+            // 写入任务，结束时丢弃 outgoing，客户端随之读到连接关闭
+            let mut write_handle = tokio::spawn(context.scope(async move {
+                let mut sequence = 0u64;
+                while let Some(mut packet) = write_receiver.recv().await {
+                    // 为每个发出的包分配递增序号
+                    sequence += 1;
+                    packet.header.sequence = sequence;
+                    if outgoing.send(packet).await.is_err() {
+                        log::error!("Write error: client disconnected");
+                        break;
+                    }
+                }
+                log::info!("Write task ended");
+            }));
+
+            // 读取任务，客户端关闭后 incoming 返回 None
+            let removed = loop {
+                let packet = tokio::select! {
+                    biased;
+                    _ = &mut stop => break true,
+                    packet = incoming.recv() => packet,
+                };
+                let Some(packet) = packet else {
+                    break false;
+                };
+                if let Some(guard) = replay_guard.as_ref()
+                    && let Err(e) = guard.lock().unwrap().check(uuid, &packet.header)
+                {
+                    log::warn!("Dropping packet: {:?}", e);
+                    continue;
+                }
+                if output_sender.send((uuid, packet)).await.is_err() {
+                    log::warn!("Output receiver closed, stopping read");
+                    break false;
+                }
+            };
+            // 停止读取后客户端再发送会失败
+            drop(incoming);
+
+            connections.lock().await.remove(&uuid);
+            if let Some(guard) = replay_guard.as_ref() {
+                guard.lock().unwrap().remove(uuid);
+            }
+            connection::finish_write(removed, &mut write_handle).await;
+            closed.notify(uuid);
+            log::info!("Connection handler ended");
+        }));
+    }
+}
+
+impl Default for MemoryServerTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryServerTransport {
+    async fn send(&self, uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        let guard = self.connections.lock().await;
+        guard.get(&uuid)
+            .ok_or_else(|| {
+                log::warn!("Attempted to send to non-existing connection UUID {}", uuid);
+                TransportError::ConnectionNotFound
+            })?
+            .send(packet)
+            .await
+            .map_err(|_| {
+                log::error!("Failed to send packet to UUID {}", uuid);
+                TransportError::SendError
+            })
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.output_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        log::info!("Closing MemoryServerTransport");
+        // 关闭主任务后排队中的连接请求被丢弃，之后的 connect 失败
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        self.accept_receiver = None;
+
+        // 关闭所有连接
+        let mut connections = self.connections.lock().await;
+        for (uuid, sender) in connections.drain() {
+            log::info!("Closing connection {}", uuid);
+            drop(sender); // 移除后写入任务与读取循环随之结束
+        }
+        Ok(())
+    }
+
+    async fn disconnect(&self, uuid: Uuid) -> Result<(), TransportError> {
+        // 移除后写入任务关闭写方向，对端读到连接结束，读取循环也随之停止
+        self.connections.lock().await.remove(&uuid).map(drop).ok_or(TransportError::ConnectionNotFound)
+    }
+
+    fn closed_connections(&mut self) -> Option<mpsc::UnboundedReceiver<Uuid>> {
+        Some(self.closed.subscribe())
+    }
+}
@@ -1,8 +1,56 @@
 mod tcp_server;
 mod hub;
 mod tcp_client;
+mod codec;
+mod connection;
+#[cfg(unix)]
+mod unix_server;
+#[cfg(unix)]
+mod unix_client;
+mod udp;
+mod udp_server;
+mod udp_client;
+mod pem;
+mod quic;
+mod quic_server;
+mod quic_client;
+mod ws;
+mod ws_server;
+mod ws_client;
+mod memory_server;
+mod memory_client;
+mod stdio;
+mod tls;
+mod tls_server;
+mod tls_client;
+mod relay;
+
+pub use tcp_server::TcpServerTransport;
+pub use tcp_client::TcpClientTransport;
+#[cfg(unix)]
+pub use unix_server::UnixServerTransport;
+#[cfg(unix)]
+pub use unix_client::UnixClientTransport;
+pub use udp::UdpConfig;
+pub use udp_server::UdpServerTransport;
+pub use udp_client::UdpClientTransport;
+pub use pem::{load_certs, load_private_key};
+pub use quic_server::QuicServerTransport;
+pub use quic_client::QuicClientTransport;
+pub use ws::WS_SUBPROTOCOL;
+pub use ws_server::WsServerTransport;
+pub use ws_client::WsClientTransport;
+pub use memory_server::{MemoryConnector, MemoryServerTransport};
+pub use memory_client::MemoryClientTransport;
+pub use stdio::StdioTransport;
+pub use tls_server::TlsServerTransport;
+pub use tls_client::TlsClientTransport;
+pub use relay::{Direction, Relay, RelayShutdown};
+pub use hub::Hub;
+pub use codec::{max_frame_size, set_max_frame_size, DEFAULT_MAX_FRAME_SIZE};
 
 use async_trait::async_trait;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 use crate::protocol::Packet;
 
@@ -10,8 +58,48 @@ use crate::protocol::Packet;
 #[async_trait]
 pub trait Transport{
     async fn send(&self, uuid: Uuid, packet: Packet) -> Result<(), TransportError>;
-    async fn receive(&mut self) -> impl Future<Output = Option<(Uuid,Packet)>>;
+    async fn receive(&mut self) -> Option<(Uuid,Packet)>;
     async fn close(&mut self) -> Result<(), TransportError>;
+
+    // 服务端主动断开某个连接，客户端传输与不支持的传输返回 ConnectionNotFound
+    async fn disconnect(&self, _uuid: Uuid) -> Result<(), TransportError> {
+        Err(TransportError::ConnectionNotFound)
+    }
+
+    // 之后结束的连接推送其 UUID，重复调用时替换之前的接收端，不支持的传输返回 None
+    fn closed_connections(&mut self) -> Option<mpsc::UnboundedReceiver<Uuid>> {
+        None
+    }
+}
+
+// 连接的对端信息，供上层做授权判断
+#[derive(Clone, Debug)]
+pub struct ConnectionInfo {
+    pub uuid: Uuid,
+    // 对端地址的文本形式，未绑定路径的 unix 客户端为 None
+    pub peer_addr: Option<String>,
+    // 本机 unix 连接的对端进程凭据
+    pub peer_cred: Option<PeerCred>,
+    // TLS 双向认证时校验通过的客户端证书身份
+    pub peer_identity: Option<PeerIdentity>,
+}
+
+// SO_PEERCRED 取得的对端进程身份
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeerCred {
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: Option<i32>,
+}
+
+// 对端证书中的身份，证书已由信任的 CA 校验
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeerIdentity {
+    // 完整的主题名，如 CN=client,O=example
+    pub subject: String,
+    pub common_name: Option<String>,
+    // 主题备用名称中的 DNS 名称
+    pub dns_names: Vec<String>,
 }
 
 // 传输错误类型
@@ -23,4 +111,4 @@ pub enum TransportError {
     ConnectionNotFound,
     SendError,
     ReceiveError,
-}
\ No newline at end of file
+}
@@ -0,0 +1,21 @@
+use std::io;
+use std::path::Path;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+// 读取 PEM 文件中的全部证书，证书链按文件中的顺序排列
+pub fn load_certs(path: impl AsRef<Path>) -> io::Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path.as_ref())
+        .map_err(io::Error::other)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io::Error::other)?;
+    if certs.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "no certificate found in PEM file"));
+    }
+    Ok(certs)
+}
+
+// 读取 PEM 文件中的第一个私钥，支持 PKCS#8、PKCS#1 与 SEC1
+pub fn load_private_key(path: impl AsRef<Path>) -> io::Result<PrivateKeyDer<'static>> {
+    PrivateKeyDer::from_pem_file(path.as_ref()).map_err(io::Error::other)
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use bytes::BytesMut;
+use quinn::{Connection, RecvStream, SendStream};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+use crate::logger::LogContext;
+use crate::protocol::{MsgType, Packet, HEADER_SIZE};
+use crate::transport::{codec, TransportError};
+
+// TLS 握手中协商的应用层协议
+pub(super) const ALPN: &[u8] = b"rummy";
+
+// 需要对端回复的包各占一个双向流，回复沿同一个流返回
+fn expects_reply(msg_type: MsgType) -> bool {
+    matches!(msg_type, MsgType::Call | MsgType::Log)
+}
+
+fn is_reply(msg_type: MsgType) -> bool {
+    matches!(msg_type, MsgType::Reply | MsgType::Error | MsgType::DeadlineExceeded)
+}
+
+pub(super) fn quic_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> TransportError {
+    TransportError::Io(io::Error::other(e))
+}
+
+// 一条 QUIC 连接，服务端与客户端共用
+// 连接迁移时 quinn 保持同一个 Connection，因此 uuid 在对端地址变化后不变
+pub(super) struct QuicConnection {
+    uuid: Uuid,
+    connection: Connection,
+    sequence: AtomicU64,
+    // 本端发起、等待回复的流，Cancel 也沿该流发送
+    call_streams: Mutex<HashMap<u64, SendStream>>,
+    // 对端发起、等待本端回复的流
+    reply_streams: Mutex<HashMap<u64, SendStream>>,
+}
+
+impl QuicConnection {
+    pub(super) fn new(uuid: Uuid, connection: Connection) -> Arc<Self> {
+        Arc::new(QuicConnection {
+            uuid,
+            connection,
+            sequence: AtomicU64::new(0),
+            call_streams: Mutex::new(HashMap::new()),
+            reply_streams: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub(super) fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    pub(super) async fn send(
+        self: &Arc<Self>,
+        mut packet: Packet,
+        output_sender: &mpsc::Sender<(Uuid, Packet)>,
+    ) -> Result<(), TransportError> {
+        // 与 TCP 一样为每个发出的包分配递增序号
+        packet.header.sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let msg_type = packet.header.msg_type;
+        let request_id = packet.header.request_id;
+
+        if is_reply(msg_type) {
+            let stream = self.reply_streams.lock().unwrap().remove(&request_id);
+            if let Some(mut stream) = stream {
+                write(&mut stream, &packet).await?;
+                return stream.finish().map_err(quic_error);
+            }
+        }
+        if expects_reply(msg_type) {
+            let (mut send, recv) = self.connection.open_bi().await.map_err(quic_error)?;
+            write(&mut send, &packet).await?;
+            self.call_streams.lock().unwrap().insert(request_id, send);
+            tokio::spawn(Arc::clone(self).read_replies(recv, request_id, output_sender.clone()));
+            return Ok(());
+        }
+        let stream = self.call_streams.lock().unwrap().remove(&request_id);
+        if let Some(mut stream) = stream {
+            write(&mut stream, &packet).await?;
+            // 取消后不再等待回复，结束发送方向让对端释放该流
+            if msg_type == MsgType::Cancel {
+                return stream.finish().map_err(quic_error);
+            }
+            self.call_streams.lock().unwrap().insert(request_id, stream);
+            return Ok(());
+        }
+        let mut stream = self.connection.open_uni().await.map_err(quic_error)?;
+        write(&mut stream, &packet).await?;
+        stream.finish().map_err(quic_error)
+    }
+
+    // 接收对端发起的流，直到连接关闭
+    pub(super) async fn serve(self: Arc<Self>, output_sender: mpsc::Sender<(Uuid, Packet)>) {
+        let context = LogContext::for_connection(self.uuid, Some(self.connection.remote_address()));
+        context.scope(async move {
+            log::info!("QUIC connection handler started");
+            loop {
+                tokio::select! {
+                    stream = self.connection.accept_bi() => match stream {
+                        Ok((send, recv)) => {
+                            tokio::spawn(Arc::clone(&self).serve_call(send, recv, output_sender.clone()));
+                        }
+                        Err(e) => {
+                            log::info!("QUIC connection closed: {}", e);
+                            break;
+                        }
+                    },
+                    stream = self.connection.accept_uni() => match stream {
+                        Ok(recv) => {
+                            tokio::spawn(Arc::clone(&self).read_stream(recv, output_sender.clone()));
+                        }
+                        Err(e) => {
+                            log::info!("QUIC connection closed: {}", e);
+                            break;
+                        }
+                    },
+                }
+            }
+        })
+        .await
+    }
+
+    async fn serve_call(self: Arc<Self>, send: SendStream, mut recv: RecvStream, output_sender: mpsc::Sender<(Uuid, Packet)>) {
+        let mut buf = BytesMut::new();
+        let packet = match codec::read_packet(&mut recv, &mut buf).await {
+            Ok(packet) => packet,
+            Err(e) => {
+                log::warn!("Failed to read call stream: {:?}", e);
+                return;
+            }
+        };
+        let request_id = packet.header.request_id;
+        let mut send = Some(send);
+        if expects_reply(packet.header.msg_type) {
+            self.reply_streams.lock().unwrap().insert(request_id, send.take().unwrap());
+        }
+        if let Some(mut send) = send {
+            let _ = send.finish();
+        }
+        if output_sender.send((self.uuid, packet)).await.is_err() {
+            return;
+        }
+        // 之后同一流上只会有 Cancel
+        while let Ok(packet) = codec::read_packet(&mut recv, &mut buf).await {
+            if output_sender.send((self.uuid, packet)).await.is_err() {
+                break;
+            }
+        }
+        // 对端已放弃该调用，不会再有回复
+        let abandoned = self.reply_streams.lock().unwrap().remove(&request_id);
+        if let Some(mut send) = abandoned {
+            let _ = send.finish();
+        }
+    }
+
+    async fn read_replies(self: Arc<Self>, mut recv: RecvStream, request_id: u64, output_sender: mpsc::Sender<(Uuid, Packet)>) {
+        let mut buf = BytesMut::new();
+        while let Ok(packet) = codec::read_packet(&mut recv, &mut buf).await {
+            let done = is_reply(packet.header.msg_type);
+            if output_sender.send((self.uuid, packet)).await.is_err() || done {
+                break;
+            }
+        }
+        let finished = self.call_streams.lock().unwrap().remove(&request_id);
+        if let Some(mut send) = finished {
+            let _ = send.finish();
+        }
+    }
+
+    async fn read_stream(self: Arc<Self>, mut recv: RecvStream, output_sender: mpsc::Sender<(Uuid, Packet)>) {
+        let mut buf = BytesMut::new();
+        while let Ok(packet) = codec::read_packet(&mut recv, &mut buf).await {
+            if output_sender.send((self.uuid, packet)).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+async fn write(stream: &mut SendStream, packet: &Packet) -> Result<(), TransportError> {
+    let mut buf = BytesMut::with_capacity(HEADER_SIZE);
+    codec::write_packet(stream, packet, &mut buf).await.map_err(TransportError::Io)
+}
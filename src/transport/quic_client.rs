@@ -0,0 +1,94 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use crate::protocol::Packet;
+use crate::transport::quic::{quic_error, QuicConnection, ALPN};
+use crate::transport::{Transport, TransportError};
+use async_trait::async_trait;
+use quinn::crypto::rustls::QuicClientConfig;
+use quinn::{ClientConfig, Endpoint};
+use rustls::pki_types::CertificateDer;
+use rustls::RootCertStore;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+pub struct QuicClientTransport {
+    uuid: Uuid,
+    endpoint: Endpoint,
+    connection: Arc<QuicConnection>,
+    output_sender: mpsc::Sender<(Uuid, Packet)>,
+    input_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+}
+
+impl QuicClientTransport {
+    // roots 为信任的 CA 证书，server_name 需与服务端证书中的名称一致
+    pub async fn connect(
+        addr: SocketAddr,
+        server_name: &str,
+        roots: Vec<CertificateDer<'static>>,
+    ) -> Result<Self, TransportError> {
+        let mut root_store = RootCertStore::empty();
+        for cert in roots {
+            root_store.add(cert).map_err(quic_error)?;
+        }
+        let mut tls = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(quic_error)?
+            .with_root_certificates(root_store)
+            .with_no_client_auth();
+        tls.alpn_protocols = vec![ALPN.to_vec()];
+        let crypto = QuicClientConfig::try_from(tls).map_err(quic_error)?;
+
+        let bind_addr: SocketAddr = if addr.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+        let mut endpoint = Endpoint::client(bind_addr).map_err(TransportError::Io)?;
+        endpoint.set_default_client_config(ClientConfig::new(Arc::new(crypto)));
+        let connection = endpoint
+            .connect(addr, server_name)
+            .map_err(quic_error)?
+            .await
+            .map_err(quic_error)?;
+
+        let uuid = Uuid::new_v4();
+        let connection = QuicConnection::new(uuid, connection);
+        let (output_sender, input_receiver) = mpsc::channel(100);
+        let main_handle = tokio::spawn(Arc::clone(&connection).serve(output_sender.clone()));
+        Ok(QuicClientTransport {
+            uuid,
+            endpoint,
+            connection,
+            output_sender,
+            input_receiver,
+            main_handle: Some(main_handle),
+        })
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    // 换用新的本地 socket，连接迁移到新地址，服务端看到的 UUID 不变
+    pub fn rebind(&self, socket: std::net::UdpSocket) -> io::Result<()> {
+        self.endpoint.rebind(socket)
+    }
+}
+
+#[async_trait]
+impl Transport for QuicClientTransport {
+    async fn send(&self, _uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        self.connection.send(packet, &self.output_sender).await
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.input_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        self.connection.connection().close(0u32.into(), b"client closed");
+        Ok(())
+    }
+}
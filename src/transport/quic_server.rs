@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use crate::protocol::Packet;
+use crate::transport::quic::{quic_error, QuicConnection, ALPN};
+use crate::transport::{ConnectionInfo, Transport, TransportError};
+use async_trait::async_trait;
+use quinn::crypto::rustls::QuicServerConfig;
+use quinn::{Endpoint, ServerConfig};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+type QuicConnections = Arc<Mutex<HashMap<Uuid, Arc<QuicConnection>>>>;
+
+// 基于 QUIC 的服务端，每个调用使用独立的流，避免大回复阻塞其它调用
+pub struct QuicServerTransport {
+    endpoint: Endpoint,
+    local_addr: SocketAddr,
+    connections: QuicConnections,
+    output_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    output_sender: mpsc::Sender<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+}
+
+impl QuicServerTransport {
+    pub async fn new(
+        addr: SocketAddr,
+        cert_chain: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+    ) -> Result<Self, TransportError> {
+        let mut tls = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(quic_error)?
+            .with_no_client_auth()
+            .with_single_cert(cert_chain, key)
+            .map_err(quic_error)?;
+        tls.alpn_protocols = vec![ALPN.to_vec()];
+        let crypto = QuicServerConfig::try_from(tls).map_err(quic_error)?;
+        let endpoint = Endpoint::server(ServerConfig::with_crypto(Arc::new(crypto)), addr).map_err(TransportError::Io)?;
+        let local_addr = endpoint.local_addr().map_err(TransportError::Io)?;
+        log::info!("Starting QUIC server on {}", local_addr);
+
+        let (output_sender, output_receiver) = mpsc::channel(100);
+        Ok(QuicServerTransport {
+            endpoint,
+            local_addr,
+            connections: Arc::new(Mutex::new(HashMap::new())),
+            output_receiver,
+            output_sender,
+            main_handle: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    // peer_addr 为对端当前地址，连接迁移后随之更新
+    pub fn connection_info(&self, uuid: Uuid) -> Option<ConnectionInfo> {
+        let connection = self.connections.lock().unwrap().get(&uuid).cloned()?;
+        Some(ConnectionInfo {
+            uuid,
+            peer_addr: Some(connection.connection().remote_address().to_string()),
+            peer_cred: None,
+            peer_identity: None,
+        })
+    }
+
+    pub fn run(&mut self) {
+        let endpoint = self.endpoint.clone();
+        let connections = Arc::clone(&self.connections);
+        let output_sender = self.output_sender.clone();
+
+        self.main_handle = Some(tokio::spawn(async move {
+            log::info!("QUIC server main loop started");
+            while let Some(incoming) = endpoint.accept().await {
+                let connections = Arc::clone(&connections);
+                let output_sender = output_sender.clone();
+                // 握手在独立任务中完成，不阻塞后续连接
+                tokio::spawn(async move {
+                    let connection = match incoming.await {
+                        Ok(connection) => connection,
+                        Err(e) => {
+                            log::warn!("QUIC handshake failed: {}", e);
+                            return;
+                        }
+                    };
+                    let uuid = Uuid::new_v4();
+                    log::info!("New QUIC connection accepted: {} - assigned UUID {}", connection.remote_address(), uuid);
+                    let connection = QuicConnection::new(uuid, connection);
+                    connections.lock().unwrap().insert(uuid, Arc::clone(&connection));
+                    connection.serve(output_sender).await;
+                    connections.lock().unwrap().remove(&uuid);
+                });
+            }
+            log::warn!("QUIC server main loop exited");
+        }));
+    }
+}
+
+#[async_trait]
+impl Transport for QuicServerTransport {
+    async fn send(&self, uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        let connection = self.connections.lock().unwrap().get(&uuid).cloned().ok_or_else(|| {
+            log::warn!("Attempted to send to non-existing connection UUID {}", uuid);
+            TransportError::ConnectionNotFound
+        })?;
+        connection.send(packet, &self.output_sender).await
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.output_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        log::info!("Closing QuicServerTransport");
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        self.endpoint.close(0u32.into(), b"server closed");
+        self.connections.lock().unwrap().clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::protocol::Packet;
+use crate::transport::{Transport, TransportError};
+use futures_util::FutureExt;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
+use uuid::Uuid;
+
+// 建立上游连接的超时，run 结束时要等待所有会话，连接不能无限挂起
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// 回复在会话队列中积压的最长时间，下游连接的写入队列一直满说明客户端没有在读，断开该会话
+const REPLY_TIMEOUT: Duration = Duration::from_millis(500);
+// 每个会话最多积压的回复数
+const MAX_PENDING_REPLIES: usize = 100;
+// 重试写出积压回复的间隔
+const RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+type Upstream = Box<dyn Transport + Send + Sync>;
+type ConnectFuture = Pin<Box<dyn Future<Output = Result<Upstream, TransportError>> + Send>>;
+type Connector = Arc<dyn Fn() -> ConnectFuture + Send + Sync>;
+type Inspector = Arc<dyn Fn(Direction, Uuid, Packet) -> Option<Packet> + Send + Sync>;
+
+// 包的转发方向
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    // 下游客户端发往上游服务端
+    Upstream,
+    // 上游服务端回复给下游客户端
+    Downstream,
+}
+
+enum SessionEvent {
+    Packet(Uuid, Packet),
+    Closed(Uuid),
+}
+
+struct Session {
+    sender: mpsc::Sender<Packet>,
+    handle: JoinHandle<()>,
+    // 下游暂时写不进的回复，按顺序写出
+    replies: VecDeque<Packet>,
+    // 队首回复第一次写不进的时间
+    stalled_since: Option<Instant>,
+}
+
+// 写出会话积压回复的结果
+enum Flush {
+    Done,
+    Pending,
+    Failed,
+    Stalled,
+}
+
+// 在其它任务中结束 Relay::run，可以克隆
+#[derive(Clone)]
+pub struct RelayShutdown(Arc<watch::Sender<bool>>);
+
+impl RelayShutdown {
+    // run 随后关闭所有会话与下游传输并返回，在 run 之前调用时 run 立即返回
+    pub fn shutdown(&self) {
+        self.0.send_replace(true);
+    }
+}
+
+// 在下游服务端传输上接受连接，为每个下游连接建立一条独立的上游连接并双向转发
+// 任意一端断开时关闭另一端，会话映射随之删除
+// 回复先进入各会话的队列，主循环只尝试写出而不等待下游，读写过慢的会话被直接断开，不会拖慢其它会话
+pub struct Relay<D> {
+    downstream: D,
+    connector: Connector,
+    inspector: Option<Inspector>,
+    shutdown: Arc<watch::Sender<bool>>,
+}
+
+impl<D: Transport + Send + Sync + 'static> Relay<D> {
+    // downstream 需已调用 run，connector 每次调用建立一条新的上游客户端连接
+    pub fn new<C, F, U>(downstream: D, connector: C) -> Self
+    where
+        C: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = Result<U, TransportError>> + Send + 'static,
+        U: Transport + Send + Sync + 'static,
+    {
+        let connector: Connector = Arc::new(move || {
+            let connecting = connector();
+            Box::pin(async move { connecting.await.map(|upstream| Box::new(upstream) as Upstream) })
+        });
+        Relay {
+            downstream,
+            connector,
+            inspector: None,
+            shutdown: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    // 需在 run 之前取得
+    pub fn shutdown_handle(&self) -> RelayShutdown {
+        RelayShutdown(Arc::clone(&self.shutdown))
+    }
+
+    // 转发前检查每个包，返回 None 时丢弃，也可以返回修改后的包
+    // uuid 为包所属的下游连接
+    pub fn set_inspector<F>(&mut self, inspector: F)
+    where
+        F: Fn(Direction, Uuid, Packet) -> Option<Packet> + Send + Sync + 'static,
+    {
+        self.inspector = Some(Arc::new(inspector));
+    }
+
+    // 下游传输关闭或调用 RelayShutdown::shutdown 后返回，此时所有上游连接与下游传输都已关闭
+    pub async fn run(mut self) -> Result<(), TransportError> {
+        let mut sessions: HashMap<Uuid, Session> = HashMap::new();
+        // 事件通道不设上限，会话任务发出事件时不必等待主循环
+        let (event_sender, mut event_receiver) = mpsc::unbounded_channel();
+        // 不支持连接结束通知的下游传输只能在回复失败时发现断开
+        let mut closed = self.downstream.closed_connections();
+        // 已被中继断开、尚未收到结束通知的下游连接，之前排队的包不再打开新会话
+        let mut disconnected: HashSet<Uuid> = HashSet::new();
+        // 有积压回复的会话
+        let mut backlogged: HashSet<Uuid> = HashSet::new();
+        let mut retry = tokio::time::interval(RETRY_INTERVAL);
+        retry.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut stop = self.shutdown.subscribe();
+
+        loop {
+            tokio::select! {
+                _ = async { drop(stop.wait_for(|stop| *stop).await) } => {
+                    log::info!("Relay shutting down");
+                    break;
+                }
+                incoming = self.downstream.receive() => {
+                    let Some((uuid, packet)) = incoming else {
+                        break;
+                    };
+                    if disconnected.contains(&uuid) {
+                        continue;
+                    }
+                    let Some(packet) = self.inspect(Direction::Upstream, uuid, packet) else {
+                        continue;
+                    };
+                    let session = sessions.entry(uuid).or_insert_with(|| {
+                        log::info!("Opening upstream session for {}", uuid);
+                        self.open_session(uuid, event_sender.clone())
+                    });
+                    match session.sender.try_send(packet) {
+                        Ok(()) => {}
+                        Err(TrySendError::Full(_)) => {
+                            log::warn!("Upstream session for {} is not keeping up, disconnecting", uuid);
+                            sessions.remove(&uuid);
+                            self.disconnect(uuid, closed.is_some(), &mut disconnected).await;
+                        }
+                        Err(TrySendError::Closed(_)) => log::warn!("Upstream session for {} already ended", uuid),
+                    }
+                }
+                event = event_receiver.recv() => match event {
+                    Some(SessionEvent::Packet(uuid, packet)) => {
+                        let Some(packet) = self.inspect(Direction::Downstream, uuid, packet) else {
+                            continue;
+                        };
+                        // 会话已被移除时丢弃
+                        let Some(session) = sessions.get_mut(&uuid) else {
+                            continue;
+                        };
+                        session.replies.push_back(packet);
+                        self.flush(uuid, &mut sessions, &mut backlogged, closed.is_some(), &mut disconnected).await;
+                    }
+                    Some(SessionEvent::Closed(uuid)) => {
+                        // 上游断开，尽量写出积压的回复后断开对应的下游连接
+                        self.flush(uuid, &mut sessions, &mut backlogged, closed.is_some(), &mut disconnected).await;
+                        backlogged.remove(&uuid);
+                        if sessions.remove(&uuid).is_some() {
+                            log::info!("Upstream for {} closed, disconnecting downstream", uuid);
+                            self.disconnect(uuid, closed.is_some(), &mut disconnected).await;
+                        }
+                    }
+                    // run 自己持有 event_sender，不会走到这里
+                    None => break,
+                },
+                _ = retry.tick(), if !backlogged.is_empty() => {
+                    let uuids: Vec<Uuid> = backlogged.iter().copied().collect();
+                    for uuid in uuids {
+                        self.flush(uuid, &mut sessions, &mut backlogged, closed.is_some(), &mut disconnected).await;
+                    }
+                }
+                Some(uuid) = async {
+                    match closed.as_mut() {
+                        Some(closed) => closed.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    // 丢弃发送端，会话任务随之关闭上游连接
+                    log::info!("Downstream {} closed, closing upstream", uuid);
+                    sessions.remove(&uuid);
+                    backlogged.remove(&uuid);
+                    disconnected.remove(&uuid);
+                }
+            }
+        }
+
+        // 丢弃发送端后各会话自行关闭上游连接，等待它们结束
+        drop(event_receiver);
+        let handles: Vec<JoinHandle<()>> = sessions.into_values().map(|session| session.handle).collect();
+        for handle in handles {
+            let _ = handle.await;
+        }
+        self.downstream.close().await
+    }
+
+    // 写出会话积压的回复，写不进或积压过久时断开该会话
+    async fn flush(
+        &self,
+        uuid: Uuid,
+        sessions: &mut HashMap<Uuid, Session>,
+        backlogged: &mut HashSet<Uuid>,
+        tracked: bool,
+        disconnected: &mut HashSet<Uuid>,
+    ) {
+        let Some(session) = sessions.get_mut(&uuid) else {
+            backlogged.remove(&uuid);
+            return;
+        };
+        match self.write_replies(uuid, session) {
+            Flush::Done => {
+                backlogged.remove(&uuid);
+            }
+            Flush::Pending => {
+                backlogged.insert(uuid);
+            }
+            Flush::Failed => {
+                backlogged.remove(&uuid);
+                sessions.remove(&uuid);
+            }
+            Flush::Stalled => {
+                log::warn!("Downstream {} is not reading replies, disconnecting", uuid);
+                backlogged.remove(&uuid);
+                sessions.remove(&uuid);
+                self.disconnect(uuid, tracked, disconnected).await;
+            }
+        }
+    }
+
+    // 每个回复只轮询一次发送，下游写入队列满时留在会话队列中稍后重试，主循环不等待
+    fn write_replies(&self, uuid: Uuid, session: &mut Session) -> Flush {
+        while let Some(packet) = session.replies.front() {
+            match self.downstream.send(uuid, packet.clone()).now_or_never() {
+                Some(Ok(())) => {
+                    session.replies.pop_front();
+                    session.stalled_since = None;
+                }
+                Some(Err(e)) => {
+                    log::warn!("Failed to relay reply to {}: {:?}", uuid, e);
+                    return Flush::Failed;
+                }
+                None => {
+                    let since = *session.stalled_since.get_or_insert_with(Instant::now);
+                    if since.elapsed() >= REPLY_TIMEOUT || session.replies.len() > MAX_PENDING_REPLIES {
+                        return Flush::Stalled;
+                    }
+                    return Flush::Pending;
+                }
+            }
+        }
+        Flush::Done
+    }
+
+    // 断开下游连接；能收到结束通知时记下 uuid，直到通知到达
+    async fn disconnect(&self, uuid: Uuid, tracked: bool, disconnected: &mut HashSet<Uuid>) {
+        if self.downstream.disconnect(uuid).await.is_ok() && tracked {
+            disconnected.insert(uuid);
+        }
+    }
+
+    fn inspect(&self, direction: Direction, uuid: Uuid, packet: Packet) -> Option<Packet> {
+        match &self.inspector {
+            Some(inspector) => inspector(direction, uuid, packet),
+            None => Some(packet),
+        }
+    }
+
+    fn open_session(&self, uuid: Uuid, events: mpsc::UnboundedSender<SessionEvent>) -> Session {
+        // 上游连接建立之前的包在通道中排队
+        let (sender, mut packets) = mpsc::channel::<Packet>(100);
+        let connector = Arc::clone(&self.connector);
+        let handle = tokio::spawn(async move {
+            let mut upstream = match tokio::time::timeout(CONNECT_TIMEOUT, connector()).await {
+                Ok(Ok(upstream)) => upstream,
+                Ok(Err(e)) => {
+                    log::warn!("Failed to connect upstream for {}: {:?}", uuid, e);
+                    let _ = events.send(SessionEvent::Closed(uuid));
+                    return;
+                }
+                Err(_) => {
+                    log::warn!("Connecting upstream for {} timed out", uuid);
+                    let _ = events.send(SessionEvent::Closed(uuid));
+                    return;
+                }
+            };
+            loop {
+                tokio::select! {
+                    packet = packets.recv() => match packet {
+                        // 客户端传输只有一条连接，目标 UUID 不起作用
+                        Some(packet) => if let Err(e) = upstream.send(Uuid::nil(), packet).await {
+                            log::warn!("Upstream send error for {}: {:?}", uuid, e);
+                            break;
+                        },
+                        // 下游已断开
+                        None => break,
+                    },
+                    incoming = upstream.receive() => match incoming {
+                        Some((_, packet)) => {
+                            if events.send(SessionEvent::Packet(uuid, packet)).is_err() {
+                                break;
+                            }
+                        }
+                        None => break,
+                    },
+                }
+            }
+            let _ = upstream.close().await;
+            let _ = events.send(SessionEvent::Closed(uuid));
+        });
+        Session {
+            sender,
+            handle,
+            replies: VecDeque::new(),
+            stalled_since: None,
+        }
+    }
+}
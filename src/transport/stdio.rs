@@ -0,0 +1,117 @@
+use std::process::Stdio;
+use std::time::Duration;
+use crate::logger::{self, LogContext};
+use crate::protocol::Packet;
+use crate::transport::{connection, Transport, TransportError};
+use async_trait::async_trait;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+// 关闭时等待子进程自行退出的时间，超时后强制结束
+const CHILD_EXIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+// 在一对读写端上收发包，帧格式与 TCP 相同
+// 两端都只有一条连接，既可以作为客户端也可以交给 RpcServer
+pub struct StdioTransport {
+    uuid: Uuid,
+    input_sender: Option<mpsc::Sender<Packet>>,
+    input_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+    child: Option<Child>,
+}
+
+impl StdioTransport {
+    pub fn new<R, W>(reader: R, writer: W) -> Self
+    where
+        R: AsyncRead + Send + 'static,
+        W: AsyncWrite + Send + 'static,
+    {
+        let uuid = Uuid::new_v4();
+        let (input_sender, output_receiver) = mpsc::channel::<Packet>(100);
+        let (output_sender, input_receiver) = mpsc::channel(100);
+        let stream = tokio::io::join(reader, writer);
+        let main_handle = connection::run_client(stream, uuid, LogContext::for_connection(uuid, None), output_sender, output_receiver);
+        StdioTransport {
+            uuid,
+            input_sender: Some(input_sender),
+            input_receiver,
+            main_handle: Some(main_handle),
+            child: None,
+        }
+    }
+
+    // 子进程一端，在自己的 stdin/stdout 上服务
+    // stdout 被帧占用，控制台日志随之改为输出到 stderr
+    pub fn stdio() -> Self {
+        logger::reserve_stdout();
+        Self::new(tokio::io::stdin(), tokio::io::stdout())
+    }
+
+    // 启动子进程并连接到它的 stdin/stdout，stderr 保持原样
+    pub fn spawn(mut command: Command) -> Result<Self, TransportError> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(TransportError::Io)?;
+        let stdin = child.stdin.take().ok_or(TransportError::MsgError)?;
+        let stdout = child.stdout.take().ok_or(TransportError::MsgError)?;
+        log::info!("Spawned child process {:?}", child.id());
+        let mut transport = Self::new(stdout, stdin);
+        transport.child = Some(child);
+        Ok(transport)
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    // 子进程的 pid，未通过 spawn 创建或已退出时为 None
+    pub fn child_id(&self) -> Option<u32> {
+        self.child.as_ref().and_then(Child::id)
+    }
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    async fn send(&self, _uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        self.input_sender
+            .as_ref()
+            .ok_or(TransportError::SendError)?
+            .send(packet)
+            .await
+            .map_err(|_| TransportError::SendError)?;
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.input_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        // 丢弃发送端后写完已排队的包，读写任务结束，对端读到 EOF；对端一直不读时超时中止
+        self.input_sender = None;
+        if let Some(mut handle) = self.main_handle.take()
+            && tokio::time::timeout(CHILD_EXIT_TIMEOUT, &mut handle).await.is_err()
+        {
+            handle.abort();
+        }
+        if let Some(mut child) = self.child.take() {
+            match tokio::time::timeout(CHILD_EXIT_TIMEOUT, child.wait()).await {
+                Ok(status) => {
+                    let status = status.map_err(TransportError::Io)?;
+                    log::info!("Child process exited with {}", status);
+                }
+                Err(_) => {
+                    log::warn!("Child process did not exit after stdin closed, killing it");
+                    child.kill().await.map_err(TransportError::Io)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
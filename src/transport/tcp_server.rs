@@ -1,29 +1,45 @@
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use crate::protocol::Packet;
-use crate::transport::{Transport, TransportError};
+use crate::logger::LogContext;
+use crate::protocol::{Packet, ReplayConfig, ReplayGuard, SharedReplayGuard, MAGIC};
+use crate::transport::connection::{ClosedNotifier, ConnectionSender};
+use crate::transport::{connection, ws, Transport, TransportError};
 use async_trait::async_trait;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::tcp::OwnedReadHalf;
 use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
 use tokio::sync::{mpsc, Mutex};
 use tokio::task::JoinHandle;
 use uuid::{Uuid};
 
+type Connections = Arc<Mutex<HashMap<Uuid, ConnectionSender>>>;
+
+// 监听端口接受的连接类型，WebSocket 携带升级请求的路径
+#[derive(Clone)]
+enum Accept {
+    Raw,
+    WebSocket(String),
+    Both(String),
+}
+
 pub struct TcpServerTransport {
     listener: Arc<Mutex<TcpListener>>,
-    connections: Arc<Mutex<HashMap<Uuid, mpsc::Sender<Packet>>>>,
+    local_addr: SocketAddr,
+    connections: Connections,
     output_receiver: mpsc::Receiver<(Uuid, Packet)>,
     main_handle: Option<JoinHandle<()>>,
     output_sender: mpsc::Sender<(Uuid, Packet)>,
+    replay_guard: Option<SharedReplayGuard>,
+    accept: Accept,
+    closed: ClosedNotifier,
 }
 
 impl TcpServerTransport {
     pub async fn new(addr: impl ToSocketAddrs) -> Result<Self, TransportError> {
-        log::info!("Starting TCP server on {:?}", addr);
         let listener = TcpListener::bind(addr)
             .await
             .map_err(TransportError::Io)?;
+        let local_addr = listener.local_addr().map_err(TransportError::Io)?;
+        log::info!("Starting TCP server on {}", local_addr);
 
         let listener = Arc::new(Mutex::new(listener));
 
@@ -31,14 +47,52 @@ impl TcpServerTransport {
 
         Ok(TcpServerTransport {
             listener,
+            local_addr,
             connections: Arc::new(Mutex::new(HashMap::new())),
             output_receiver,
             main_handle: None,
             output_sender,
+            replay_guard: None,
+            accept: Accept::Raw,
+            closed: ClosedNotifier::default(),
         })
     }
 
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    // 开启过期与重放检查，需在 run 之前调用
+    pub fn set_replay_protection(&mut self, config: ReplayConfig) {
+        self.replay_guard = Some(ReplayGuard::shared(config));
+    }
+
+    // 同一端口同时接受 WebSocket 连接，按首字节区分，需在 run 之前调用
+    // 共用端口时原生客户端发出第一个包后连接才会建立
+    pub fn set_websocket(&mut self, path: impl Into<String>) {
+        self.accept = Accept::Both(path.into());
+    }
+
+    // 只接受 WebSocket 连接，供 WsServerTransport 使用
+    pub(super) fn set_websocket_only(&mut self, path: String) {
+        self.accept = Accept::WebSocket(path);
+    }
+
+    // 向所有连接发送同一个包，各连接共享 payload 缓冲区
+    pub async fn broadcast(&self, packet: Packet) -> Result<(), TransportError> {
+        let connections = self.connections.lock().await;
+        for (uuid, sender) in connections.iter() {
+            if sender.send(packet.clone()).await.is_err() {
+                log::warn!("Failed to broadcast packet to UUID {}", uuid);
+            }
+        }
+        Ok(())
+    }
+
     pub fn run(&mut self) {
+        let replay_guard = self.replay_guard.clone();
+        let accept = self.accept.clone();
+        let closed = self.closed.clone();
         let listener = Arc::clone(&self.listener);
         let connections = Arc::clone(&self.connections);
         let output_sender = self.output_sender.clone();
@@ -47,23 +101,20 @@ impl TcpServerTransport {
             log::info!("TCP server main loop started");
             loop {
                 let stream = {
-                    let mut locked = listener.lock().await;
+                    let locked = listener.lock().await;
                     locked.accept().await
                 };
 
                 match stream {
                     Ok((stream, peer_addr)) => {
-                        let uuid = Uuid::new_v4();
-                        log::info!("New connection accepted: {} - assigned UUID {}", peer_addr, uuid);
-                        let (write_sender, write_receiver) = mpsc::channel(100);
-                        connections.lock().await.insert(uuid, write_sender);
-
-                        Self::handle_connection(
+                        Self::accept_connection(
                             stream,
-                            uuid,
+                            peer_addr,
+                            accept.clone(),
                             output_sender.clone(),
-                            write_receiver,
                             Arc::clone(&connections),
+                            replay_guard.clone(),
+                            closed.clone(),
                         );
                     }
                     Err(e) => {
@@ -76,65 +127,54 @@ impl TcpServerTransport {
         }));
     }
 
-    fn handle_connection(
+    fn accept_connection(
         stream: TcpStream,
-        uuid: Uuid,
+        peer_addr: SocketAddr,
+        accept: Accept,
         output_sender: mpsc::Sender<(Uuid, Packet)>,
-        mut write_receiver: mpsc::Receiver<Packet>,
-        connections: Arc<Mutex<HashMap<Uuid, mpsc::Sender<Packet>>>>
-    ) -> JoinHandle<()> {
+        connections: Connections,
+        replay_guard: Option<SharedReplayGuard>,
+        closed: ClosedNotifier,
+    ) {
+        let uuid = Uuid::new_v4();
+        log::info!("New connection accepted: {} - assigned UUID {}", peer_addr, uuid);
+        let context = LogContext::for_connection(uuid, Some(peer_addr));
         tokio::spawn(async move {
-            log::info!("Connection handler started for UUID {}", uuid);
-
-            let (mut read_half, mut write_half) = stream.into_split();
-
-            // 写入任务
-            let write_handle = tokio::spawn(async move {
-                while let Some(packet) = write_receiver.recv().await {
-                    if let Err(e) = write_half.write_all(&packet.to_bytes()).await {
-                        log::error!("Write error: {}", e);
-                        break;
+            let websocket_path = match accept {
+                Accept::Raw => None,
+                Accept::WebSocket(path) => Some(path),
+                // 原生协议以魔数 rum3 开头，其余按 HTTP 升级请求处理
+                // 对端连上后一直不发数据时超时断开，不让空闲连接长期占用任务与文件描述符
+                Accept::Both(path) => {
+                    let mut first = [0u8; 1];
+                    match tokio::time::timeout(ws::HANDSHAKE_TIMEOUT, stream.peek(&mut first)).await {
+                        Ok(Ok(1)) if first[0] == MAGIC[0] => None,
+                        Ok(Ok(1)) => Some(path),
+                        Ok(_) => return,
+                        Err(_) => {
+                            log::warn!("Connection from {} sent nothing within {:?}, closing", peer_addr, ws::HANDSHAKE_TIMEOUT);
+                            return;
+                        }
                     }
                 }
-                log::info!("Write task ended for connection {}", uuid);
-                // 关闭连接时移除
-                connections.lock().await.remove(&uuid);
-                log::info!("Connection {} removed from active connections", uuid);
-            });
+            };
 
-            // 读取任务
-            loop {
-                match Self::read_packet(&mut read_half).await {
-                    Ok(packet) => {
-                        if output_sender.send((uuid, packet)).await.is_err() {
-                            log::warn!("Output receiver closed, stopping read for connection {}", uuid);
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Read error on connection {}: {:?}", uuid, e);
-                        break;
-                    }
+            let (write_sender, write_receiver) = connection::channel();
+            match websocket_path {
+                None => {
+                    connections.lock().await.insert(uuid, write_sender);
+                    connection::serve_connection(stream, uuid, context, output_sender, write_receiver, connections, replay_guard, closed);
                 }
+                Some(path) => match tokio::time::timeout(ws::HANDSHAKE_TIMEOUT, ws::accept(stream, &path)).await {
+                    Ok(Ok(ws)) => {
+                        connections.lock().await.insert(uuid, write_sender);
+                        ws::serve_ws_connection(ws, uuid, context, output_sender, write_receiver, connections, replay_guard, closed);
+                    }
+                    Ok(Err(e)) => log::warn!("WebSocket handshake with {} failed: {}", peer_addr, e),
+                    Err(_) => log::warn!("WebSocket handshake with {} timed out", peer_addr),
+                },
             }
-
-            write_handle.abort();
-            log::info!("Connection handler ended for UUID {}", uuid);
-        })
-    }
-
-    async fn read_packet(stream: &mut OwnedReadHalf) -> Result<Packet, TransportError> {
-        let mut header = [0u8; 64];
-        stream.read_exact(&mut header).await.map_err(|_| TransportError::ReceiveError)?;
-
-        let payload_len = u32::from_le_bytes(header[16..20].try_into().unwrap()) as usize;
-        let mut packet_data = Vec::with_capacity(64 + payload_len);
-        packet_data.extend_from_slice(&header);
-
-        packet_data.resize(64 + payload_len, 0);
-        stream.read_exact(&mut packet_data[64..]).await.map_err(|_| TransportError::ReceiveError)?;
-
-        Packet::from_bytes(&packet_data).map_err(|_| TransportError::MsgError)
+        });
     }
 }
 
@@ -156,8 +196,8 @@ impl Transport for TcpServerTransport {
             })
     }
 
-    async fn receive(&mut self) -> impl Future<Output = Option<(Uuid,Packet)>> {
-        self.output_receiver.recv()
+    async fn receive(&mut self) -> Option<(Uuid,Packet)> {
+        self.output_receiver.recv().await
     }
 
     async fn close(&mut self) -> Result<(), TransportError> {
@@ -172,8 +212,17 @@ impl Transport for TcpServerTransport {
         let mut connections = self.connections.lock().await;
         for (uuid, sender) in connections.drain() {
             log::info!("Closing connection {}", uuid);
-            drop(sender); // 关闭发送端会终止写入任务
+            drop(sender); // 移除后写入任务与读取循环随之结束
         }
         Ok(())
     }
+
+    async fn disconnect(&self, uuid: Uuid) -> Result<(), TransportError> {
+        // 移除后写入任务关闭写方向，对端读到连接结束，读取循环也随之停止
+        self.connections.lock().await.remove(&uuid).map(drop).ok_or(TransportError::ConnectionNotFound)
+    }
+
+    fn closed_connections(&mut self) -> Option<mpsc::UnboundedReceiver<Uuid>> {
+        Some(self.closed.subscribe())
+    }
 }
\ No newline at end of file
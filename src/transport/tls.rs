@@ -0,0 +1,58 @@
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::CertificateDer;
+use rustls::RootCertStore;
+use x509_parser::extensions::GeneralName;
+use crate::transport::{PeerIdentity, TransportError};
+
+// 握手超过该时间未完成的连接直接断开
+pub(super) const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(super) fn tls_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> TransportError {
+    TransportError::Io(io::Error::other(e))
+}
+
+pub(super) fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+pub(super) fn root_store(roots: Vec<CertificateDer<'static>>) -> Result<RootCertStore, TransportError> {
+    let mut store = RootCertStore::empty();
+    for cert in roots {
+        store.add(cert).map_err(tls_error)?;
+    }
+    Ok(store)
+}
+
+// 从终端实体证书中取出主题名与 DNS 备用名称，解析失败时返回 None
+pub(super) fn peer_identity(cert: &CertificateDer<'_>) -> Option<PeerIdentity> {
+    let (_, cert) = x509_parser::parse_x509_certificate(cert.as_ref()).ok()?;
+    let common_name = cert
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|name| name.as_str().ok())
+        .map(str::to_string);
+    let dns_names = cert
+        .subject_alternative_name()
+        .ok()
+        .flatten()
+        .map(|san| {
+            san.value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(dns) => Some(dns.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(PeerIdentity {
+        subject: cert.subject().to_string(),
+        common_name,
+        dns_names,
+    })
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use crate::logger::LogContext;
+use crate::protocol::Packet;
+use crate::transport::tls::{provider, root_store, tls_error, HANDSHAKE_TIMEOUT};
+use crate::transport::{connection, Transport, TransportError};
+use async_trait::async_trait;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::ClientConfig;
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_rustls::TlsConnector;
+use uuid::Uuid;
+
+pub struct TlsClientTransport {
+    uuid: Uuid,
+    input_sender: mpsc::Sender<Packet>,
+    input_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+}
+
+impl TlsClientTransport {
+    // roots 为信任的 CA 证书，server_name 需与服务端证书中的名称一致
+    pub async fn connect(
+        addr: impl ToSocketAddrs,
+        server_name: &str,
+        roots: Vec<CertificateDer<'static>>,
+    ) -> Result<Self, TransportError> {
+        let config = ClientConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .with_root_certificates(root_store(roots)?)
+            .with_no_client_auth();
+        Self::connect_with(addr, server_name, config).await
+    }
+
+    // 服务端要求双向认证时，额外出示客户端证书链与私钥
+    pub async fn connect_with_client_cert(
+        addr: impl ToSocketAddrs,
+        server_name: &str,
+        roots: Vec<CertificateDer<'static>>,
+        cert_chain: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+    ) -> Result<Self, TransportError> {
+        let config = ClientConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .with_root_certificates(root_store(roots)?)
+            .with_client_auth_cert(cert_chain, key)
+            .map_err(tls_error)?;
+        Self::connect_with(addr, server_name, config).await
+    }
+
+    async fn connect_with(
+        addr: impl ToSocketAddrs,
+        server_name: &str,
+        config: ClientConfig,
+    ) -> Result<Self, TransportError> {
+        let server_name = ServerName::try_from(server_name.to_string()).map_err(tls_error)?;
+        let stream = TcpStream::connect(addr)
+            .await
+            .map_err(TransportError::Io)?;
+        let peer_addr = stream.peer_addr().ok();
+        let connector = TlsConnector::from(Arc::new(config));
+        let stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, connector.connect(server_name, stream))
+            .await
+            .map_err(tls_error)?
+            .map_err(TransportError::Io)?;
+
+        // 客户端只有一条连接，用固定的 UUID 标识
+        let uuid = Uuid::new_v4();
+        let (input_sender, output_receiver) = mpsc::channel::<Packet>(100);
+        let (output_sender, input_receiver) = mpsc::channel(100);
+        let context = LogContext::for_connection(uuid, peer_addr);
+        let main_handle = connection::run_client(stream, uuid, context, output_sender, output_receiver);
+        Ok(TlsClientTransport {
+            uuid,
+            input_sender,
+            input_receiver,
+            main_handle: Some(main_handle),
+        })
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
+
+#[async_trait]
+impl Transport for TlsClientTransport {
+    async fn send(&self, _uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        self.input_sender.send(packet)
+            .await
+            .map_err(|_| TransportError::SendError)?;
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.input_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        // 读写在同一个任务中，中止后连接随之断开
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        Ok(())
+    }
+}
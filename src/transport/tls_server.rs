@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use crate::logger::LogContext;
+use crate::protocol::{Packet, ReplayConfig, ReplayGuard, SharedReplayGuard};
+use crate::transport::tls::{peer_identity, provider, root_store, tls_error, HANDSHAKE_TIMEOUT};
+use crate::transport::connection::{ClosedNotifier, ConnectionSender};
+use crate::transport::{connection, ConnectionInfo, Transport, TransportError};
+use async_trait::async_trait;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::ServerConfig;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio_rustls::TlsAcceptor;
+use uuid::Uuid;
+
+struct TlsConnection {
+    sender: ConnectionSender,
+    info: ConnectionInfo,
+}
+
+type Connections = Arc<Mutex<HashMap<Uuid, TlsConnection>>>;
+
+// 标准 TLS 包裹的 TCP 服务端，用于不能使用自定义 RSA/AES 握手的环境
+pub struct TlsServerTransport {
+    listener: Arc<Mutex<TcpListener>>,
+    local_addr: SocketAddr,
+    cert_chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    acceptor: TlsAcceptor,
+    connections: Connections,
+    output_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+    output_sender: mpsc::Sender<(Uuid, Packet)>,
+    replay_guard: Option<SharedReplayGuard>,
+    closed: ClosedNotifier,
+}
+
+impl TlsServerTransport {
+    pub async fn new(
+        addr: impl ToSocketAddrs,
+        cert_chain: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+    ) -> Result<Self, TransportError> {
+        let config = ServerConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .with_no_client_auth()
+            .with_single_cert(cert_chain.clone(), key.clone_key())
+            .map_err(tls_error)?;
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(TransportError::Io)?;
+        let local_addr = listener.local_addr().map_err(TransportError::Io)?;
+        log::info!("Starting TLS server on {}", local_addr);
+
+        let (output_sender, output_receiver) = mpsc::channel(100);
+
+        Ok(TlsServerTransport {
+            listener: Arc::new(Mutex::new(listener)),
+            local_addr,
+            cert_chain,
+            key,
+            acceptor: TlsAcceptor::from(Arc::new(config)),
+            connections: Arc::new(Mutex::new(HashMap::new())),
+            output_receiver,
+            main_handle: None,
+            output_sender,
+            replay_guard: None,
+            closed: ClosedNotifier::default(),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    // 要求客户端出示由 roots 中的 CA 签发的证书，需在 run 之前调用
+    pub fn require_client_auth(&mut self, roots: Vec<CertificateDer<'static>>) -> Result<(), TransportError> {
+        let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(root_store(roots)?), provider())
+            .build()
+            .map_err(tls_error)?;
+        let config = ServerConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(self.cert_chain.clone(), self.key.clone_key())
+            .map_err(tls_error)?;
+        self.acceptor = TlsAcceptor::from(Arc::new(config));
+        Ok(())
+    }
+
+    // 开启过期与重放检查，需在 run 之前调用
+    pub fn set_replay_protection(&mut self, config: ReplayConfig) {
+        self.replay_guard = Some(ReplayGuard::shared(config));
+    }
+
+    // 连接的对端信息，双向认证时包含客户端证书身份，连接已断开时返回 None
+    pub async fn connection_info(&self, uuid: Uuid) -> Option<ConnectionInfo> {
+        self.connections.lock().await.get(&uuid).map(|connection| connection.info.clone())
+    }
+
+    // 向所有连接发送同一个包，各连接共享 payload 缓冲区
+    pub async fn broadcast(&self, packet: Packet) -> Result<(), TransportError> {
+        let connections = self.connections.lock().await;
+        for (uuid, connection) in connections.iter() {
+            if connection.sender.send(packet.clone()).await.is_err() {
+                log::warn!("Failed to broadcast packet to UUID {}", uuid);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn run(&mut self) {
+        let replay_guard = self.replay_guard.clone();
+        let closed = self.closed.clone();
+        let acceptor = self.acceptor.clone();
+        let listener = Arc::clone(&self.listener);
+        let connections = Arc::clone(&self.connections);
+        let output_sender = self.output_sender.clone();
+
+        self.main_handle = Some(tokio::spawn(async move {
+            log::info!("TLS server main loop started");
+            loop {
+                let stream = {
+                    let locked = listener.lock().await;
+                    locked.accept().await
+                };
+
+                match stream {
+                    Ok((stream, peer_addr)) => {
+                        Self::accept_connection(
+                            stream,
+                            peer_addr,
+                            acceptor.clone(),
+                            output_sender.clone(),
+                            Arc::clone(&connections),
+                            replay_guard.clone(),
+                            closed.clone(),
+                        );
+                    }
+                    Err(e) => {
+                        log::error!("Accept error: {}", e);
+                        break;
+                    }
+                }
+            }
+            log::warn!("TLS server main loop exited");
+        }));
+    }
+
+    // 握手在独立任务中完成，不阻塞后续连接
+    fn accept_connection(
+        stream: TcpStream,
+        peer_addr: SocketAddr,
+        acceptor: TlsAcceptor,
+        output_sender: mpsc::Sender<(Uuid, Packet)>,
+        connections: Connections,
+        replay_guard: Option<SharedReplayGuard>,
+        closed: ClosedNotifier,
+    ) {
+        tokio::spawn(async move {
+            let stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(e)) => {
+                    log::warn!("TLS handshake with {} failed: {}", peer_addr, e);
+                    return;
+                }
+                Err(_) => {
+                    log::warn!("TLS handshake with {} timed out", peer_addr);
+                    return;
+                }
+            };
+            let uuid = Uuid::new_v4();
+            let peer_identity = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .and_then(peer_identity);
+            log::info!("New TLS connection accepted: {} ({:?}) - assigned UUID {}", peer_addr, peer_identity, uuid);
+            let info = ConnectionInfo {
+                uuid,
+                peer_addr: Some(peer_addr.to_string()),
+                peer_cred: None,
+                peer_identity,
+            };
+            let (write_sender, write_receiver) = connection::channel();
+            connections.lock().await.insert(uuid, TlsConnection { sender: write_sender, info });
+            connection::serve_connection(
+                stream,
+                uuid,
+                LogContext::for_connection(uuid, Some(peer_addr)),
+                output_sender,
+                write_receiver,
+                connections,
+                replay_guard,
+                closed,
+            );
+        });
+    }
+}
+
+#[async_trait]
+impl Transport for TlsServerTransport {
+    async fn send(&self, uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        let guard = self.connections.lock().await;
+        guard.get(&uuid)
+            .ok_or_else(|| {
+                log::warn!("Attempted to send to non-existing connection UUID {}", uuid);
+                TransportError::ConnectionNotFound
+            })?
+            .sender
+            .send(packet)
+            .await
+            .map_err(|_| {
+                log::error!("Failed to send packet to UUID {}", uuid);
+                TransportError::SendError
+            })
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.output_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        log::info!("Closing TlsServerTransport");
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        let mut connections = self.connections.lock().await;
+        for (uuid, connection) in connections.drain() {
+            log::info!("Closing connection {}", uuid);
+            drop(connection.sender); // 移除后写入任务与读取循环随之结束
+        }
+        Ok(())
+    }
+
+    async fn disconnect(&self, uuid: Uuid) -> Result<(), TransportError> {
+        // 移除后写入任务关闭写方向，对端读到连接结束，读取循环也随之停止
+        self.connections.lock().await.remove(&uuid).map(drop).ok_or(TransportError::ConnectionNotFound)
+    }
+
+    fn closed_connections(&mut self) -> Option<mpsc::UnboundedReceiver<Uuid>> {
+        Some(self.closed.subscribe())
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use uuid::Uuid;
+use crate::protocol::{Packet, SequenceWindow, HEADER_SIZE};
+use crate::transport::connection::ClosedNotifier;
+use crate::transport::TransportError;
+
+// 数据报前缀：类型 1 字节 + 序号 8 字节 + 前两者的 CRC32 4 字节，之后是完整的 Packet
+// ACK 只有前缀，不受 Packet 校验和保护，因此前缀单独校验
+const PREFIX_SIZE: usize = 13;
+const KIND_DATA: u8 = 0;
+// 需要对端回复 ACK 的数据
+const KIND_RELIABLE: u8 = 1;
+const KIND_ACK: u8 = 2;
+const MAX_DATAGRAM: usize = 65507;
+
+// UDP 传输配置
+#[derive(Clone, Copy, Debug)]
+pub struct UdpConfig {
+    // Transport::send 是否默认要求确认，send_with 可逐包指定
+    pub reliable: bool,
+    pub retransmit_interval: Duration,
+    // 超过重传次数后放弃该包
+    pub max_retransmits: u32,
+    // 服务端在该时间内未收到对端数据时关闭虚拟连接，None 表示永不过期
+    pub idle_timeout: Option<Duration>,
+    // 服务端最多同时保持的虚拟连接数，达到上限后忽略新地址发来的数据
+    pub max_peers: usize,
+}
+
+impl Default for UdpConfig {
+    fn default() -> Self {
+        UdpConfig {
+            reliable: false,
+            retransmit_interval: Duration::from_millis(200),
+            max_retransmits: 5,
+            idle_timeout: Some(Duration::from_secs(60)),
+            max_peers: 1024,
+        }
+    }
+}
+
+pub(super) type PeerAddrs = Arc<Mutex<HashMap<Uuid, SocketAddr>>>;
+
+pub(super) struct Outgoing {
+    pub(super) addr: SocketAddr,
+    pub(super) packet: Packet,
+    pub(super) reliable: bool,
+}
+
+// 每个远端地址对应的虚拟连接
+struct Peer {
+    uuid: Uuid,
+    next_sequence: u64,
+    received: SequenceWindow,
+    unacked: BTreeMap<u64, Pending>,
+    last_seen: Instant,
+}
+
+struct Pending {
+    datagram: Bytes,
+    sent_at: Instant,
+    attempts: u32,
+}
+
+impl Peer {
+    fn new(uuid: Uuid) -> Self {
+        Peer {
+            uuid,
+            next_sequence: 1,
+            received: SequenceWindow::default(),
+            unacked: BTreeMap::new(),
+            last_seen: Instant::now(),
+        }
+    }
+}
+
+// 服务端与客户端共用的收发任务，独占 socket 与全部虚拟连接状态
+pub(super) struct Endpoint {
+    pub(super) socket: UdpSocket,
+    pub(super) config: UdpConfig,
+    // 是否为未知地址新建虚拟连接，客户端只与固定的服务端通信
+    pub(super) accept: bool,
+    pub(super) addrs: PeerAddrs,
+    // 虚拟连接过期时推送其 UUID
+    pub(super) closed: ClosedNotifier,
+}
+
+impl Endpoint {
+    pub(super) fn spawn(
+        self,
+        mut outgoing: mpsc::Receiver<Outgoing>,
+        incoming: mpsc::Sender<(Uuid, Packet)>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut peers: HashMap<SocketAddr, Peer> = self
+                .addrs
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(uuid, addr)| (*addr, Peer::new(*uuid)))
+                .collect();
+            let mut ticker = tokio::time::interval(self.config.retransmit_interval);
+            let mut buf = vec![0u8; MAX_DATAGRAM];
+            loop {
+                tokio::select! {
+                    received = self.socket.recv_from(&mut buf) => match received {
+                        Ok((len, addr)) => {
+                            if !self.handle_datagram(&mut peers, &buf[..len], addr, &incoming).await {
+                                break;
+                            }
+                        }
+                        Err(e) => log::warn!("UDP receive error: {}", e),
+                    },
+                    message = outgoing.recv() => match message {
+                        Some(message) => self.send_packet(&mut peers, message).await,
+                        None => break,
+                    },
+                    _ = ticker.tick() => self.retransmit(&mut peers).await,
+                }
+            }
+        })
+    }
+
+    // 上层已关闭接收端时返回 false
+    async fn handle_datagram(
+        &self,
+        peers: &mut HashMap<SocketAddr, Peer>,
+        datagram: &[u8],
+        addr: SocketAddr,
+        incoming: &mpsc::Sender<(Uuid, Packet)>,
+    ) -> bool {
+        let Some((kind, sequence, body)) = decode_prefix(datagram) else {
+            log::warn!("Dropping corrupted datagram from {}", addr);
+            return true;
+        };
+        // 先校验再确认，损坏的可靠包既不回复 ACK 也不记为已收到，对端会继续重传
+        let packet = match kind {
+            KIND_ACK => None,
+            KIND_DATA | KIND_RELIABLE => match Packet::decode(Bytes::copy_from_slice(body)) {
+                Ok(packet) if packet.header.sequence == sequence => Some(packet),
+                Ok(_) => {
+                    log::warn!("Dropping datagram {} from {}: sequence mismatch", sequence, addr);
+                    return true;
+                }
+                Err(e) => {
+                    log::warn!("Dropping invalid packet from {}: {:?}", addr, e);
+                    return true;
+                }
+            },
+            _ => {
+                log::warn!("Unknown datagram kind {} from {}", kind, addr);
+                return true;
+            }
+        };
+
+        let entry_count = peers.len();
+        let peer = match peers.entry(addr) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                if !self.accept || kind == KIND_ACK {
+                    log::debug!("Ignoring datagram from unknown peer {}", addr);
+                    return true;
+                }
+                if entry_count >= self.config.max_peers {
+                    log::warn!("Ignoring datagram from {}: UDP peer limit {} reached", addr, self.config.max_peers);
+                    return true;
+                }
+                let uuid = Uuid::new_v4();
+                log::info!("New UDP peer: {} - assigned UUID {}", addr, uuid);
+                self.addrs.lock().unwrap().insert(uuid, addr);
+                entry.insert(Peer::new(uuid))
+            }
+        };
+        peer.last_seen = Instant::now();
+
+        let Some(packet) = packet else {
+            peer.unacked.remove(&sequence);
+            return true;
+        };
+        // 重复包也要回复 ACK，对端可能没收到上一次的 ACK
+        if peer.received.seen(sequence) {
+            log::debug!("Dropping duplicate datagram {} from {}", sequence, addr);
+            self.ack(kind, sequence, addr).await;
+            return true;
+        }
+        // 收发任务由所有对端共用，不能等待上层读取；接收队列满时丢弃且不确认，对端稍后重传
+        match incoming.try_send((peer.uuid, packet)) {
+            Ok(()) => {
+                peer.received.accept(sequence);
+                self.ack(kind, sequence, addr).await;
+                true
+            }
+            Err(TrySendError::Full(_)) => {
+                log::warn!("Receive queue full, dropping datagram {} from {}", sequence, addr);
+                true
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+
+    async fn ack(&self, kind: u8, sequence: u64, addr: SocketAddr) {
+        if kind != KIND_RELIABLE {
+            return;
+        }
+        let ack = encode(KIND_ACK, sequence, None);
+        if let Err(e) = self.socket.send_to(&ack, addr).await {
+            log::warn!("Failed to ack datagram to {}: {}", addr, e);
+        }
+    }
+
+    async fn send_packet(&self, peers: &mut HashMap<SocketAddr, Peer>, message: Outgoing) {
+        let Some(peer) = peers.get_mut(&message.addr) else {
+            log::warn!("Dropping packet for closed UDP peer {}", message.addr);
+            return;
+        };
+        let mut packet = message.packet;
+        let sequence = peer.next_sequence;
+        peer.next_sequence += 1;
+        packet.header.sequence = sequence;
+        let kind = if message.reliable { KIND_RELIABLE } else { KIND_DATA };
+        let datagram = encode(kind, sequence, Some(&packet));
+        if let Err(e) = self.socket.send_to(&datagram, message.addr).await {
+            log::warn!("UDP send to {} failed: {}", message.addr, e);
+        }
+        // 发送失败的可靠包同样交给重传
+        if message.reliable {
+            peer.unacked.insert(sequence, Pending {
+                datagram,
+                sent_at: Instant::now(),
+                attempts: 0,
+            });
+        }
+    }
+
+    async fn retransmit(&self, peers: &mut HashMap<SocketAddr, Peer>) {
+        let now = Instant::now();
+        let mut expired = Vec::new();
+        for (addr, peer) in peers.iter_mut() {
+            let mut abandoned = Vec::new();
+            for (sequence, pending) in peer.unacked.iter_mut() {
+                if now.duration_since(pending.sent_at) < self.config.retransmit_interval {
+                    continue;
+                }
+                if pending.attempts >= self.config.max_retransmits {
+                    abandoned.push(*sequence);
+                    continue;
+                }
+                pending.attempts += 1;
+                pending.sent_at = now;
+                if let Err(e) = self.socket.send_to(&pending.datagram, addr).await {
+                    log::warn!("UDP retransmit to {} failed: {}", addr, e);
+                }
+            }
+            for sequence in abandoned {
+                log::warn!("Giving up on datagram {} to {} after {} retransmits", sequence, addr, self.config.max_retransmits);
+                peer.unacked.remove(&sequence);
+            }
+            if self.accept
+                && let Some(idle_timeout) = self.config.idle_timeout
+                && now.duration_since(peer.last_seen) > idle_timeout
+            {
+                expired.push(*addr);
+            }
+        }
+        // 服务端回收长时间无数据的虚拟连接
+        for addr in expired {
+            if let Some(peer) = peers.remove(&addr) {
+                log::info!("UDP peer {} ({}) idle, closing", addr, peer.uuid);
+                self.addrs.lock().unwrap().remove(&peer.uuid);
+                self.closed.notify(peer.uuid);
+            }
+        }
+    }
+}
+
+fn encode(kind: u8, sequence: u64, packet: Option<&Packet>) -> Bytes {
+    let mut buf = BytesMut::with_capacity(PREFIX_SIZE + packet.map_or(0, |packet| HEADER_SIZE + packet.payload.len()));
+    buf.put_u8(kind);
+    buf.put_u64_le(sequence);
+    let checksum = crc32fast::hash(&buf);
+    buf.put_u32_le(checksum);
+    if let Some(packet) = packet {
+        packet.encode_header(&mut buf);
+        buf.put_slice(&packet.payload);
+    }
+    buf.freeze()
+}
+
+// 返回 (类型, 序号, 其后的内容)，长度不足或前缀校验失败时返回 None
+fn decode_prefix(datagram: &[u8]) -> Option<(u8, u64, &[u8])> {
+    if datagram.len() < PREFIX_SIZE {
+        return None;
+    }
+    let (mut prefix, body) = datagram.split_at(PREFIX_SIZE);
+    if crc32fast::hash(&prefix[..PREFIX_SIZE - 4]) != u32::from_le_bytes(prefix[PREFIX_SIZE - 4..].try_into().unwrap()) {
+        return None;
+    }
+    Some((prefix.get_u8(), prefix.get_u64_le(), body))
+}
+
+// 查找虚拟连接的地址并交给收发任务
+pub(super) async fn enqueue(
+    addrs: &PeerAddrs,
+    outgoing: &mpsc::Sender<Outgoing>,
+    uuid: Uuid,
+    packet: Packet,
+    reliable: bool,
+) -> Result<(), TransportError> {
+    if PREFIX_SIZE + HEADER_SIZE + packet.payload.len() > MAX_DATAGRAM {
+        return Err(TransportError::SendError);
+    }
+    let addr = addrs.lock().unwrap().get(&uuid).copied().ok_or(TransportError::ConnectionNotFound)?;
+    outgoing
+        .send(Outgoing { addr, packet, reliable })
+        .await
+        .map_err(|_| TransportError::SendError)
+}
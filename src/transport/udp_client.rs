@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::protocol::Packet;
+use crate::transport::connection::ClosedNotifier;
+use crate::transport::udp::{self, Endpoint, Outgoing, PeerAddrs, UdpConfig};
+use crate::transport::{Transport, TransportError};
+use async_trait::async_trait;
+use tokio::net::{lookup_host, ToSocketAddrs, UdpSocket};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+// 只与一个服务端地址通信，忽略其它来源的数据报
+pub struct UdpClientTransport {
+    uuid: Uuid,
+    config: UdpConfig,
+    addrs: PeerAddrs,
+    outgoing: mpsc::Sender<Outgoing>,
+    input_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+}
+
+impl UdpClientTransport {
+    pub async fn connect(addr: impl ToSocketAddrs, config: UdpConfig) -> Result<Self, TransportError> {
+        let server_addr = lookup_host(addr)
+            .await
+            .map_err(TransportError::Io)?
+            .next()
+            .ok_or(TransportError::ConnectionNotFound)?;
+        let bind_addr = if server_addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(bind_addr).await.map_err(TransportError::Io)?;
+
+        let uuid = Uuid::new_v4();
+        let addrs: PeerAddrs = Arc::new(Mutex::new(HashMap::from([(uuid, server_addr)])));
+        let (outgoing, outgoing_receiver) = mpsc::channel(100);
+        let (input_sender, input_receiver) = mpsc::channel(100);
+        let endpoint = Endpoint {
+            socket,
+            config,
+            accept: false,
+            addrs: Arc::clone(&addrs),
+            closed: ClosedNotifier::default(),
+        };
+        let main_handle = endpoint.spawn(outgoing_receiver, input_sender);
+        Ok(UdpClientTransport {
+            uuid,
+            config,
+            addrs,
+            outgoing,
+            input_receiver,
+            main_handle: Some(main_handle),
+        })
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    // 逐包指定是否需要确认
+    pub async fn send_with(&self, packet: Packet, reliable: bool) -> Result<(), TransportError> {
+        udp::enqueue(&self.addrs, &self.outgoing, self.uuid, packet, reliable).await
+    }
+}
+
+#[async_trait]
+impl Transport for UdpClientTransport {
+    async fn send(&self, _uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        self.send_with(packet, self.config.reliable).await
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.input_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        Ok(())
+    }
+}
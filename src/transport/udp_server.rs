@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use crate::protocol::Packet;
+use crate::transport::connection::ClosedNotifier;
+use crate::transport::udp::{self, Endpoint, Outgoing, PeerAddrs, UdpConfig};
+use crate::transport::{ConnectionInfo, Transport, TransportError};
+use async_trait::async_trait;
+use tokio::net::{ToSocketAddrs, UdpSocket};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+// 每个发来数据的远端地址视为一条虚拟连接并分配 UUID
+pub struct UdpServerTransport {
+    socket: Option<UdpSocket>,
+    local_addr: SocketAddr,
+    config: UdpConfig,
+    addrs: PeerAddrs,
+    outgoing: mpsc::Sender<Outgoing>,
+    outgoing_receiver: Option<mpsc::Receiver<Outgoing>>,
+    output_sender: mpsc::Sender<(Uuid, Packet)>,
+    output_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+    closed: ClosedNotifier,
+}
+
+impl UdpServerTransport {
+    pub async fn new(addr: impl ToSocketAddrs, config: UdpConfig) -> Result<Self, TransportError> {
+        let socket = UdpSocket::bind(addr).await.map_err(TransportError::Io)?;
+        let local_addr = socket.local_addr().map_err(TransportError::Io)?;
+        log::info!("Starting UDP server on {}", local_addr);
+
+        let (outgoing, outgoing_receiver) = mpsc::channel(100);
+        let (output_sender, output_receiver) = mpsc::channel(100);
+        Ok(UdpServerTransport {
+            socket: Some(socket),
+            local_addr,
+            config,
+            addrs: Arc::new(Mutex::new(HashMap::new())),
+            outgoing,
+            outgoing_receiver: Some(outgoing_receiver),
+            output_sender,
+            output_receiver,
+            main_handle: None,
+            closed: ClosedNotifier::default(),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn run(&mut self) {
+        let (Some(socket), Some(outgoing_receiver)) = (self.socket.take(), self.outgoing_receiver.take()) else {
+            log::warn!("UDP server is already running");
+            return;
+        };
+        let endpoint = Endpoint {
+            socket,
+            config: self.config,
+            accept: true,
+            addrs: Arc::clone(&self.addrs),
+            closed: self.closed.clone(),
+        };
+        self.main_handle = Some(endpoint.spawn(outgoing_receiver, self.output_sender.clone()));
+    }
+
+    // 逐包指定是否需要确认
+    pub async fn send_with(&self, uuid: Uuid, packet: Packet, reliable: bool) -> Result<(), TransportError> {
+        udp::enqueue(&self.addrs, &self.outgoing, uuid, packet, reliable).await
+    }
+
+    // 向所有虚拟连接发送同一个包，各连接共享 payload 缓冲区
+    pub async fn broadcast(&self, packet: Packet) -> Result<(), TransportError> {
+        let uuids: Vec<Uuid> = self.addrs.lock().unwrap().keys().copied().collect();
+        for uuid in uuids {
+            if self.send(uuid, packet.clone()).await.is_err() {
+                log::warn!("Failed to broadcast packet to UUID {}", uuid);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn connection_info(&self, uuid: Uuid) -> Option<ConnectionInfo> {
+        let addr = self.addrs.lock().unwrap().get(&uuid).copied()?;
+        Some(ConnectionInfo {
+            uuid,
+            peer_addr: Some(addr.to_string()),
+            peer_cred: None,
+            peer_identity: None,
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for UdpServerTransport {
+    async fn send(&self, uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        self.send_with(uuid, packet, self.config.reliable).await
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.output_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        log::info!("Closing UdpServerTransport");
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        self.addrs.lock().unwrap().clear();
+        Ok(())
+    }
+
+    // 空闲超时的虚拟连接
+    fn closed_connections(&mut self) -> Option<mpsc::UnboundedReceiver<Uuid>> {
+        Some(self.closed.subscribe())
+    }
+}
@@ -0,0 +1,73 @@
+use std::io;
+use std::path::Path;
+use crate::logger::LogContext;
+use crate::protocol::Packet;
+use crate::transport::{connection, unix_server, Transport, TransportError};
+use async_trait::async_trait;
+use tokio::net::UnixStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+// 路径规则与 UnixServerTransport 相同，@ 开头表示抽象命名空间
+pub struct UnixClientTransport {
+    uuid: Uuid,
+    input_sender: mpsc::Sender<Packet>,
+    input_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+}
+
+impl UnixClientTransport {
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self, TransportError> {
+        let stream = connect_stream(path.as_ref()).await.map_err(TransportError::Io)?;
+
+        let uuid = Uuid::new_v4();
+        let (input_sender, output_receiver) = mpsc::channel::<Packet>(100);
+        let (output_sender, input_receiver) = mpsc::channel(100);
+        let context = LogContext::for_connection(uuid, None);
+        let main_handle = connection::run_client(stream, uuid, context, output_sender, output_receiver);
+        Ok(UnixClientTransport {
+            uuid,
+            input_sender,
+            input_receiver,
+            main_handle: Some(main_handle),
+        })
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
+
+#[async_trait]
+impl Transport for UnixClientTransport {
+    async fn send(&self, _uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        self.input_sender.send(packet)
+            .await
+            .map_err(|_| TransportError::SendError)
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.input_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        Ok(())
+    }
+}
+
+async fn connect_stream(path: &Path) -> io::Result<UnixStream> {
+    let Some(name) = unix_server::abstract_name(path) else {
+        return UnixStream::connect(path).await;
+    };
+    // 抽象命名空间只能通过标准库连接，放到阻塞线程池中执行
+    let addr = unix_server::abstract_addr(name)?;
+    let stream = tokio::task::spawn_blocking(move || std::os::unix::net::UnixStream::connect_addr(&addr))
+        .await
+        .map_err(io::Error::other)??;
+    stream.set_nonblocking(true)?;
+    UnixStream::from_std(stream)
+}
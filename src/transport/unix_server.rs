@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::io;
+#[cfg(target_os = "linux")]
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use crate::logger::LogContext;
+use crate::protocol::{Packet, ReplayConfig, ReplayGuard, SharedReplayGuard};
+use crate::transport::connection::{ClosedNotifier, ConnectionSender};
+use crate::transport::{connection, ConnectionInfo, PeerCred, Transport, TransportError};
+use async_trait::async_trait;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+struct UnixConnection {
+    sender: ConnectionSender,
+    info: ConnectionInfo,
+}
+
+// 本机进程间通信，路径以 @ 开头时使用 Linux 抽象命名空间，不在文件系统中留下套接字文件
+pub struct UnixServerTransport {
+    listener: Arc<Mutex<UnixListener>>,
+    path: PathBuf,
+    connections: Arc<Mutex<HashMap<Uuid, UnixConnection>>>,
+    output_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+    output_sender: mpsc::Sender<(Uuid, Packet)>,
+    replay_guard: Option<SharedReplayGuard>,
+    closed: ClosedNotifier,
+}
+
+impl UnixServerTransport {
+    pub async fn new(path: impl AsRef<Path>) -> Result<Self, TransportError> {
+        let path = path.as_ref().to_path_buf();
+        let listener = bind(&path).await.map_err(TransportError::Io)?;
+        log::info!("Starting unix server on {}", path.display());
+
+        let (output_sender, output_receiver) = mpsc::channel(100);
+
+        Ok(UnixServerTransport {
+            listener: Arc::new(Mutex::new(listener)),
+            path,
+            connections: Arc::new(Mutex::new(HashMap::new())),
+            output_receiver,
+            main_handle: None,
+            output_sender,
+            replay_guard: None,
+            closed: ClosedNotifier::default(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // 开启过期与重放检查，需在 run 之前调用
+    pub fn set_replay_protection(&mut self, config: ReplayConfig) {
+        self.replay_guard = Some(ReplayGuard::shared(config));
+    }
+
+    // 连接的对端信息，连接已断开时返回 None
+    pub async fn connection_info(&self, uuid: Uuid) -> Option<ConnectionInfo> {
+        self.connections.lock().await.get(&uuid).map(|connection| connection.info.clone())
+    }
+
+    // 向所有连接发送同一个包，各连接共享 payload 缓冲区
+    pub async fn broadcast(&self, packet: Packet) -> Result<(), TransportError> {
+        let connections = self.connections.lock().await;
+        for (uuid, connection) in connections.iter() {
+            if connection.sender.send(packet.clone()).await.is_err() {
+                log::warn!("Failed to broadcast packet to UUID {}", uuid);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn run(&mut self) {
+        let replay_guard = self.replay_guard.clone();
+        let closed = self.closed.clone();
+        let listener = Arc::clone(&self.listener);
+        let connections = Arc::clone(&self.connections);
+        let output_sender = self.output_sender.clone();
+
+        self.main_handle = Some(tokio::spawn(async move {
+            log::info!("Unix server main loop started");
+            loop {
+                let stream = {
+                    let locked = listener.lock().await;
+                    locked.accept().await
+                };
+
+                match stream {
+                    Ok((stream, _)) => {
+                        let uuid = Uuid::new_v4();
+                        let info = connection_info(uuid, &stream);
+                        log::info!("New unix connection accepted: {:?} - assigned UUID {}", info.peer_cred, uuid);
+                        let (write_sender, write_receiver) = connection::channel();
+                        connections.lock().await.insert(uuid, UnixConnection { sender: write_sender, info });
+
+                        connection::serve_connection(
+                            stream,
+                            uuid,
+                            LogContext::for_connection(uuid, None),
+                            output_sender.clone(),
+                            write_receiver,
+                            Arc::clone(&connections),
+                            replay_guard.clone(),
+                            closed.clone(),
+                        );
+                    }
+                    Err(e) => {
+                        log::error!("Accept error: {}", e);
+                        break;
+                    }
+                }
+            }
+            log::warn!("Unix server main loop exited");
+        }));
+    }
+}
+
+#[async_trait]
+impl Transport for UnixServerTransport {
+    async fn send(&self, uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        let guard = self.connections.lock().await;
+        guard.get(&uuid)
+            .ok_or_else(|| {
+                log::warn!("Attempted to send to non-existing connection UUID {}", uuid);
+                TransportError::ConnectionNotFound
+            })?
+            .sender
+            .send(packet)
+            .await
+            .map_err(|_| {
+                log::error!("Failed to send packet to UUID {}", uuid);
+                TransportError::SendError
+            })
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.output_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        log::info!("Closing UnixServerTransport");
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        let mut connections = self.connections.lock().await;
+        for (uuid, connection) in connections.drain() {
+            log::info!("Closing connection {}", uuid);
+            drop(connection.sender); // 移除后写入任务与读取循环随之结束
+        }
+        // 文件系统路径的套接字需要手动删除
+        if !is_abstract(&self.path) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+        Ok(())
+    }
+
+    async fn disconnect(&self, uuid: Uuid) -> Result<(), TransportError> {
+        // 移除后写入任务关闭写方向，对端读到连接结束，读取循环也随之停止
+        self.connections.lock().await.remove(&uuid).map(drop).ok_or(TransportError::ConnectionNotFound)
+    }
+
+    fn closed_connections(&mut self) -> Option<mpsc::UnboundedReceiver<Uuid>> {
+        Some(self.closed.subscribe())
+    }
+}
+
+fn is_abstract(path: &Path) -> bool {
+    abstract_name(path).is_some()
+}
+
+// @ 开头的路径去掉 @ 后的抽象命名空间名称
+pub(super) fn abstract_name(path: &Path) -> Option<&[u8]> {
+    path.as_os_str().as_bytes().strip_prefix(b"@")
+}
+
+#[cfg(target_os = "linux")]
+pub(super) fn abstract_addr(name: &[u8]) -> io::Result<SocketAddr> {
+    SocketAddr::from_abstract_name(name)
+}
+
+// 抽象命名空间是 Linux 特有的
+#[cfg(not(target_os = "linux"))]
+pub(super) fn abstract_addr(_name: &[u8]) -> io::Result<SocketAddr> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "abstract unix sockets require Linux"))
+}
+
+async fn bind(path: &Path) -> io::Result<UnixListener> {
+    if let Some(name) = abstract_name(path) {
+        let listener = std::os::unix::net::UnixListener::bind_addr(&abstract_addr(name)?)?;
+        listener.set_nonblocking(true)?;
+        return UnixListener::from_std(listener);
+    }
+    // 上次进程异常退出留下的套接字文件无人监听时删除
+    if path.exists()
+        && let Err(e) = UnixStream::connect(path).await
+        && e.kind() == io::ErrorKind::ConnectionRefused
+    {
+        std::fs::remove_file(path)?;
+    }
+    UnixListener::bind(path)
+}
+
+fn connection_info(uuid: Uuid, stream: &UnixStream) -> ConnectionInfo {
+    let peer_cred = stream.peer_cred().ok().map(|cred| PeerCred {
+        uid: cred.uid(),
+        gid: cred.gid(),
+        pid: cred.pid(),
+    });
+    let peer_addr = stream
+        .peer_addr()
+        .ok()
+        .and_then(|addr| addr.as_pathname().map(|path| path.display().to_string()));
+    ConnectionInfo { uuid, peer_addr, peer_cred, peer_identity: None }
+}
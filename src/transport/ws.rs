@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use bytes::{BufMut, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::{HeaderValue, StatusCode};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::WebSocketStream;
+use uuid::Uuid;
+use crate::logger::LogContext;
+use crate::protocol::{Packet, SharedReplayGuard, HEADER_SIZE};
+use crate::transport::connection::{self, ClosedNotifier, ConnectionReceiver};
+
+// 握手时协商的子协议名，客户端必须在 Sec-WebSocket-Protocol 中声明
+pub const WS_SUBPROTOCOL: &str = "rummy.v2";
+const PROTOCOL_HEADER: &str = "Sec-WebSocket-Protocol";
+
+// 只接受指定路径且声明了 rummy 子协议的升级请求
+// 服务端等待对端发出首个字节与完成升级握手的最长时间，与 TLS 握手一致
+pub(crate) const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) async fn accept<S>(stream: S, path: &str) -> Result<WebSocketStream<S>, WsError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // 错误响应的类型由 tungstenite 的回调签名决定
+    #[allow(clippy::result_large_err)]
+    let callback = |request: &Request, mut response: Response| -> Result<Response, ErrorResponse> {
+        if request.uri().path() != path {
+            return Err(error_response(StatusCode::NOT_FOUND));
+        }
+        let offered = request
+            .headers()
+            .get_all(PROTOCOL_HEADER)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|protocol| protocol.trim() == WS_SUBPROTOCOL);
+        if !offered {
+            return Err(error_response(StatusCode::BAD_REQUEST));
+        }
+        response.headers_mut().insert(PROTOCOL_HEADER, HeaderValue::from_static(WS_SUBPROTOCOL));
+        Ok(response)
+    };
+    tokio_tungstenite::accept_hdr_async(stream, callback).await
+}
+
+fn error_response(status: StatusCode) -> ErrorResponse {
+    let mut response = ErrorResponse::new(None);
+    *response.status_mut() = status;
+    response
+}
+
+// 每个二进制帧承载一个完整的包
+fn to_message(packet: &Packet) -> Message {
+    let mut buf = BytesMut::with_capacity(HEADER_SIZE + packet.payload.len());
+    packet.encode_header(&mut buf);
+    buf.put_slice(&packet.payload);
+    Message::Binary(buf.freeze())
+}
+
+// 与 connection::serve_connection 相同，只是以 WebSocket 帧收发
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn serve_ws_connection<S, V>(
+    ws: WebSocketStream<S>,
+    uuid: Uuid,
+    context: LogContext,
+    output_sender: mpsc::Sender<(Uuid, Packet)>,
+    write_receiver: ConnectionReceiver,
+    connections: Arc<Mutex<HashMap<Uuid, V>>>,
+    replay_guard: Option<SharedReplayGuard>,
+    closed: ClosedNotifier,
+) -> JoinHandle<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    V: Send + 'static,
+{
+    tokio::spawn(context.clone().scope(async move {
+        log::info!("WebSocket connection handler started");
+        let (mut sink, mut stream) = ws.split();
+        let ConnectionReceiver { packets: mut write_receiver, mut stop } = write_receiver;
+
+        // 写入任务
+        let mut write_handle = tokio::spawn(context.scope(async move {
+            let mut sequence = 0u64;
+            while let Some(mut packet) = write_receiver.recv().await {
+                // 为每个发出的包分配递增序号
+                sequence += 1;
+                packet.header.sequence = sequence;
+                if let Err(e) = sink.send(to_message(&packet)).await {
+                    log::error!("Write error: {}", e);
+                    break;
+                }
+            }
+            let _ = sink.close().await;
+            log::info!("Write task ended");
+        }));
+
+        // 读取任务
+        let removed = loop {
+            let message = tokio::select! {
+                biased;
+                _ = &mut stop => break true,
+                message = stream.next() => message,
+            };
+            let Some(message) = message else {
+                break false;
+            };
+            let data = match message {
+                Ok(Message::Binary(data)) => data,
+                Ok(Message::Close(_)) => break false,
+                Ok(Message::Text(_)) => {
+                    log::warn!("Ignoring text frame");
+                    continue;
+                }
+                Ok(_) => continue,
+                Err(e) => {
+                    log::error!("Read error: {}", e);
+                    break false;
+                }
+            };
+            let packet = match Packet::decode(data) {
+                Ok(packet) => packet,
+                Err(e) => {
+                    log::warn!("Dropping invalid packet: {:?}", e);
+                    continue;
+                }
+            };
+            if let Some(guard) = replay_guard.as_ref()
+                && let Err(e) = guard.lock().unwrap().check(uuid, &packet.header)
+            {
+                log::warn!("Dropping packet: {:?}", e);
+                continue;
+            }
+            if output_sender.send((uuid, packet)).await.is_err() {
+                log::warn!("Output receiver closed, stopping read");
+                break false;
+            }
+        };
+
+        connections.lock().await.remove(&uuid);
+        if let Some(guard) = replay_guard.as_ref() {
+            guard.lock().unwrap().remove(uuid);
+        }
+        connection::finish_write(removed, &mut write_handle).await;
+        closed.notify(uuid);
+        log::info!("WebSocket connection handler ended");
+    }))
+}
+
+// 客户端的唯一 WebSocket 连接
+pub(crate) fn run_ws_client<S>(
+    ws: WebSocketStream<S>,
+    uuid: Uuid,
+    context: LogContext,
+    output_sender: mpsc::Sender<(Uuid, Packet)>,
+    mut packet_receiver: mpsc::Receiver<Packet>,
+) -> JoinHandle<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    tokio::spawn(context.scope(async move {
+        let (mut sink, mut stream) = ws.split();
+        // 与 run_client 一样读写在同一个任务中，close 中止该任务时连接随之关闭
+        let write = async {
+            let mut sequence = 0u64;
+            while let Some(mut packet) = packet_receiver.recv().await {
+                sequence += 1;
+                packet.header.sequence = sequence;
+                if let Err(e) = sink.send(to_message(&packet)).await {
+                    log::error!("Write error: {}", e);
+                    break;
+                }
+            }
+            let _ = sink.close().await;
+        };
+
+        let read = async {
+            while let Some(message) = stream.next().await {
+                match message {
+                    Ok(Message::Binary(data)) => match Packet::decode(data) {
+                        Ok(packet) => {
+                            if output_sender.send((uuid, packet)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => log::warn!("Dropping invalid packet: {:?}", e),
+                    },
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        log::error!("Read error: {}", e);
+                        break;
+                    }
+                }
+            }
+        };
+
+        tokio::select! {
+            _ = write => {}
+            _ = read => {}
+        }
+    }))
+}
@@ -0,0 +1,67 @@
+use std::io;
+use crate::logger::LogContext;
+use crate::protocol::Packet;
+use crate::transport::ws::{self, WS_SUBPROTOCOL};
+use crate::transport::{Transport, TransportError};
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use uuid::Uuid;
+
+pub struct WsClientTransport {
+    uuid: Uuid,
+    input_sender: mpsc::Sender<Packet>,
+    input_receiver: mpsc::Receiver<(Uuid, Packet)>,
+    main_handle: Option<JoinHandle<()>>,
+}
+
+impl WsClientTransport {
+    // url 形如 ws://host:port/rummy
+    pub async fn connect(url: &str) -> Result<Self, TransportError> {
+        let mut request = url.into_client_request().map_err(|e| TransportError::Io(io::Error::other(e)))?;
+        request
+            .headers_mut()
+            .insert("Sec-WebSocket-Protocol", HeaderValue::from_static(WS_SUBPROTOCOL));
+        let (stream, _) = tokio_tungstenite::connect_async(request)
+            .await
+            .map_err(|e| TransportError::Io(io::Error::other(e)))?;
+
+        let uuid = Uuid::new_v4();
+        let (input_sender, output_receiver) = mpsc::channel::<Packet>(100);
+        let (output_sender, input_receiver) = mpsc::channel(100);
+        let context = LogContext::for_connection(uuid, None);
+        let main_handle = ws::run_ws_client(stream, uuid, context, output_sender, output_receiver);
+        Ok(WsClientTransport {
+            uuid,
+            input_sender,
+            input_receiver,
+            main_handle: Some(main_handle),
+        })
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
+
+#[async_trait]
+impl Transport for WsClientTransport {
+    async fn send(&self, _uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        self.input_sender.send(packet)
+            .await
+            .map_err(|_| TransportError::SendError)
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.input_receiver.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        if let Some(handle) = self.main_handle.take() {
+            handle.abort();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use std::net::SocketAddr;
+use crate::protocol::{Packet, ReplayConfig};
+use crate::transport::{TcpServerTransport, Transport, TransportError};
+use async_trait::async_trait;
+use tokio::net::ToSocketAddrs;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+// 只接受 WebSocket 升级请求的服务端，连接管理与 TcpServerTransport 相同
+// 需要与原生 TCP 共用端口时改用 TcpServerTransport::set_websocket
+pub struct WsServerTransport {
+    inner: TcpServerTransport,
+}
+
+impl WsServerTransport {
+    // path 为升级请求的路径，例如 "/rummy"
+    pub async fn new(addr: impl ToSocketAddrs, path: impl Into<String>) -> Result<Self, TransportError> {
+        let mut inner = TcpServerTransport::new(addr).await?;
+        inner.set_websocket_only(path.into());
+        Ok(WsServerTransport { inner })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.inner.local_addr()
+    }
+
+    // 开启过期与重放检查，需在 run 之前调用
+    pub fn set_replay_protection(&mut self, config: ReplayConfig) {
+        self.inner.set_replay_protection(config);
+    }
+
+    pub async fn broadcast(&self, packet: Packet) -> Result<(), TransportError> {
+        self.inner.broadcast(packet).await
+    }
+
+    pub fn run(&mut self) {
+        self.inner.run();
+    }
+}
+
+#[async_trait]
+impl Transport for WsServerTransport {
+    async fn send(&self, uuid: Uuid, packet: Packet) -> Result<(), TransportError> {
+        self.inner.send(uuid, packet).await
+    }
+
+    async fn receive(&mut self) -> Option<(Uuid, Packet)> {
+        self.inner.receive().await
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        self.inner.close().await
+    }
+
+    async fn disconnect(&self, uuid: Uuid) -> Result<(), TransportError> {
+        self.inner.disconnect(uuid).await
+    }
+
+    fn closed_connections(&mut self) -> Option<mpsc::UnboundedReceiver<Uuid>> {
+        self.inner.closed_connections()
+    }
+}
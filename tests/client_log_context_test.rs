@@ -0,0 +1,32 @@
+use std::time::Duration;
+use log::LevelFilter;
+use rummy::logger::{init_logger_with, LoggerConfig, RingBuffer, SinkConfig};
+use rummy::protocol::{Packet, PacketHeader};
+use rummy::transport::{TcpClientTransport, TcpServerTransport, Transport};
+
+#[tokio::test]
+async fn client_errors_carry_connection_context() {
+    let path = std::env::temp_dir().join(format!("rummy-client-context-{}.log", uuid::Uuid::new_v4()));
+    let ring = RingBuffer::new(100);
+    let mut config = LoggerConfig::new(LevelFilter::Info, &path);
+    config.console.enabled = false;
+    config.sinks.push(SinkConfig::new(ring.sink()));
+    let guard = init_logger_with(config).expect("Logger init failed");
+
+    let mut server = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr();
+    server.run();
+    let mut client = TcpClientTransport::connect(addr).await.unwrap();
+    client.send(uuid::Uuid::nil(), Packet::new(PacketHeader::from_payload(b"hi", 0), &b"hi"[..])).await.unwrap();
+    let (session, _) = server.receive().await.unwrap();
+
+    // 服务端断开后客户端读到连接结束，错误日志带上客户端的 uuid
+    server.disconnect(session).await.unwrap();
+    assert!(tokio::time::timeout(Duration::from_secs(1), client.receive()).await.unwrap().is_none());
+    log::logger().flush();
+    let expected = format!("uuid={}", client.uuid());
+    assert!(ring.last(100).iter().any(|line| line.contains("Read error") && line.contains(&expected)));
+
+    drop(guard);
+    std::fs::remove_file(path).unwrap();
+}
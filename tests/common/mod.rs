@@ -0,0 +1,38 @@
+// 各集成测试共用的服务端启动函数，每个测试文件只用到其中一部分
+#![allow(dead_code)]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+use bytes::Bytes;
+use rummy::rpc::{CallContext, HandlerResult, RpcServer};
+use rummy::transport::TcpServerTransport;
+
+// 在随机端口上启动 TCP RPC 服务端
+pub async fn start_server<F, Fut>(handler: F) -> SocketAddr
+where
+    F: Fn(CallContext, Bytes) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = HandlerResult> + Send + 'static,
+{
+    let mut transport = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    let addr = transport.local_addr();
+    transport.run();
+    tokio::spawn(RpcServer::with_handler(transport, handler).serve());
+    addr
+}
+
+// 回复自己名字的服务端
+pub async fn start_named(name: &'static str) -> String {
+    start_server(move |_, _: Bytes| async move { Ok(name.as_bytes().to_vec()) }).await.to_string()
+}
+
+// 收到 slow 时延迟回复，其余立即回复自己的名字
+pub async fn start_slow(name: &'static str) -> String {
+    start_server(move |_, payload: Bytes| async move {
+        if payload == b"slow"[..] {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+        Ok(name.as_bytes().to_vec())
+    })
+    .await
+    .to_string()
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+use rummy::protocol::{Packet, PacketHeader};
+use rummy::transport::{TcpClientTransport, TcpServerTransport, Transport, TransportError, WsClientTransport};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use uuid::Uuid;
+
+fn packet(payload: &'static [u8]) -> Packet {
+    let mut header = PacketHeader::from_payload(payload, 0);
+    header.sequence = 1;
+    Packet::new(header, payload)
+}
+
+// 对端断开后服务端移除连接，之后的发送返回 ConnectionNotFound
+async fn wait_removed(server: &TcpServerTransport, uuid: Uuid) {
+    for _ in 0..100 {
+        if let Err(TransportError::ConnectionNotFound) = server.send(uuid, packet(b"bye")).await {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("connection {} was not removed", uuid);
+}
+
+#[tokio::test]
+async fn tcp_connection_is_removed_when_peer_disconnects() {
+    let mut server = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr();
+    server.run();
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(&packet(b"hello").to_bytes()).await.unwrap();
+    let (uuid, _) = server.receive().await.unwrap();
+    drop(stream);
+    wait_removed(&server, uuid).await;
+}
+
+#[tokio::test]
+async fn websocket_connection_is_removed_when_peer_disconnects() {
+    let mut server = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    server.set_websocket("/rummy");
+    let addr = server.local_addr();
+    server.run();
+
+    let client = WsClientTransport::connect(&format!("ws://{}/rummy", addr)).await.unwrap();
+    client.send(Uuid::nil(), packet(b"hello")).await.unwrap();
+    let (uuid, _) = server.receive().await.unwrap();
+    drop(client);
+    wait_removed(&server, uuid).await;
+}
+
+// 客户端 close 后即使传输对象仍未丢弃，连接也随之断开
+#[tokio::test]
+async fn tcp_client_close_disconnects() {
+    let mut server = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr();
+    server.run();
+
+    let mut client = TcpClientTransport::connect(addr).await.unwrap();
+    client.send(Uuid::nil(), packet(b"hello")).await.unwrap();
+    let (uuid, _) = server.receive().await.unwrap();
+    client.close().await.unwrap();
+    wait_removed(&server, uuid).await;
+    assert!(client.send(Uuid::nil(), packet(b"again")).await.is_err());
+}
+
+#[tokio::test]
+async fn websocket_client_close_disconnects() {
+    let mut server = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    server.set_websocket("/rummy");
+    let addr = server.local_addr();
+    server.run();
+
+    let mut client = WsClientTransport::connect(&format!("ws://{}/rummy", addr)).await.unwrap();
+    client.send(Uuid::nil(), packet(b"hello")).await.unwrap();
+    let (uuid, _) = server.receive().await.unwrap();
+    client.close().await.unwrap();
+    wait_removed(&server, uuid).await;
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+use rummy::discovery::{Discovery, FileDiscovery};
+use rummy::rpc::{PoolConfig, RpcPool};
+
+mod common;
+use common::start_named;
+
+fn temp_path(extension: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("rummy-discovery-{}.{}", uuid::Uuid::new_v4(), extension))
+}
+
+#[tokio::test]
+async fn file_changes_are_pushed_to_subscribers() {
+    let path = temp_path("toml");
+    std::fs::write(&path, "echo = [\"127.0.0.1:7000\"]\n").unwrap();
+    let discovery = FileDiscovery::with_interval(&path, Duration::from_millis(20)).unwrap();
+    let mut echo = discovery.subscribe("echo");
+    let mut missing = discovery.subscribe("missing");
+    assert_eq!(*echo.borrow(), vec!["127.0.0.1:7000".to_string()]);
+    assert!(missing.borrow().is_empty());
+
+    std::fs::write(&path, "echo = [\"127.0.0.1:7000\", \"127.0.0.1:7001\"]\nmissing = [\"127.0.0.1:8000\"]\n").unwrap();
+    tokio::time::timeout(Duration::from_secs(2), echo.changed()).await.unwrap().unwrap();
+    assert_eq!(echo.borrow_and_update().len(), 2);
+    tokio::time::timeout(Duration::from_secs(2), missing.changed()).await.unwrap().unwrap();
+    assert_eq!(*missing.borrow_and_update(), vec!["127.0.0.1:8000".to_string()]);
+
+    // 无法解析的内容被忽略，保留上一次的结果
+    std::fs::write(&path, "echo = [").unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(discovery.services()["echo"].len(), 2);
+    std::fs::remove_file(path).unwrap();
+
+    // JSON 文件按扩展名识别
+    let path = temp_path("json");
+    std::fs::write(&path, r#"{"echo": ["127.0.0.1:9000"]}"#).unwrap();
+    let discovery = FileDiscovery::new(&path).unwrap();
+    assert_eq!(*discovery.subscribe("echo").borrow(), vec!["127.0.0.1:9000".to_string()]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn pool_follows_discovery_file() {
+    let a = start_named("a").await;
+    let b = start_named("b").await;
+    let path = temp_path("toml");
+    std::fs::write(&path, format!("echo = [\"{}\"]\n", a)).unwrap();
+    let discovery = FileDiscovery::with_interval(&path, Duration::from_millis(20)).unwrap();
+
+    let pool = RpcPool::discover(&discovery, "echo", PoolConfig::default()).await;
+    assert_eq!(pool.call(Vec::new()).await.unwrap(), &b"a"[..]);
+
+    std::fs::write(&path, format!("echo = [\"{}\"]\n", b)).unwrap();
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while pool.healthy_endpoints() != vec![b.clone()] {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(pool.call(Vec::new()).await.unwrap(), &b"b"[..]);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+use rummy::protocol::{Packet, PacketHeader};
+use rummy::transport::{set_max_frame_size, TcpServerTransport, Transport, DEFAULT_MAX_FRAME_SIZE};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+
+#[tokio::test]
+async fn oversized_frame_closes_connection() {
+    let mut server = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr();
+    let mut closed = server.closed_connections().unwrap();
+    server.run();
+
+    // 只发包头，声明的长度远超上限，服务端不等 payload 直接断开
+    let mut header = Packet::new(PacketHeader::from_payload(b"", 0), &b""[..]).to_bytes().to_vec();
+    header[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(&header).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(1), closed.recv()).await.unwrap().unwrap();
+
+    // 上限可以调整，之后读到的包按新上限检查
+    set_max_frame_size(4);
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(&Packet::new(PacketHeader::from_payload(b"payload", 0), &b"payload"[..]).to_bytes()).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(1), closed.recv()).await.unwrap().unwrap();
+    set_max_frame_size(DEFAULT_MAX_FRAME_SIZE);
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(&Packet::new(PacketHeader::from_payload(b"payload", 0), &b"payload"[..]).to_bytes()).await.unwrap();
+    let (_, packet) = tokio::time::timeout(Duration::from_secs(1), server.receive()).await.unwrap().unwrap();
+    assert_eq!(packet.payload, &b"payload"[..]);
+}
@@ -0,0 +1,38 @@
+use log::LevelFilter;
+use rummy::logger::LevelDirectives;
+
+#[test]
+fn parses_directives() {
+    let directives = LevelDirectives::parse(" rummy::transport=debug , rummy::encrypt=bogus,warn,rummy::rpc", LevelFilter::Info);
+    // 不带目标的级别覆盖默认级别
+    assert_eq!(directives.level_for("other"), LevelFilter::Warn);
+    assert_eq!(directives.level_for("rummy::transport"), LevelFilter::Debug);
+    // 无法解析的级别被忽略，沿用默认级别
+    assert_eq!(directives.level_for("rummy::encrypt"), LevelFilter::Warn);
+    // 只写目标名时打开全部日志
+    assert_eq!(directives.level_for("rummy::rpc::client"), LevelFilter::Trace);
+
+    // 同一目标出现多次时以最后一条为准
+    let directives = LevelDirectives::parse("rummy=debug,rummy=error", LevelFilter::Info);
+    assert_eq!(directives.level_for("rummy"), LevelFilter::Error);
+    assert_eq!(directives.max_level(), LevelFilter::Info);
+
+    let directives = LevelDirectives::parse("", LevelFilter::Info);
+    assert_eq!(directives.level_for("rummy"), LevelFilter::Info);
+}
+
+#[test]
+fn matches_longest_prefix() {
+    let directives = LevelDirectives::parse("rummy=warn,rummy::transport::tcp=trace,rummy::transport=info", LevelFilter::Error);
+    assert_eq!(directives.level_for("rummy::transport::tcp_server"), LevelFilter::Trace);
+    assert_eq!(directives.level_for("rummy::transport::udp"), LevelFilter::Info);
+    assert_eq!(directives.level_for("rummy::logger"), LevelFilter::Warn);
+    assert_eq!(directives.level_for("tokio"), LevelFilter::Error);
+
+    // 最宽松的级别决定全局上限
+    assert_eq!(directives.max_level(), LevelFilter::Trace);
+    let mut quiet = LevelDirectives::parse("rummy=warn", LevelFilter::Off);
+    assert_eq!(quiet.max_level(), LevelFilter::Warn);
+    quiet.set_default(LevelFilter::Debug);
+    assert_eq!(quiet.max_level(), LevelFilter::Debug);
+}
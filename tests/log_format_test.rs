@@ -0,0 +1,55 @@
+use log::{Level, Record};
+use rummy::logger::{LogContext, LogFormat, LogRecord};
+
+fn capture(message: &str) -> LogRecord {
+    let fields = [("peer", "127.0.0.1:9000"), ("attempt", "2")];
+    LogRecord::capture(
+        &Record::builder()
+            .args(format_args!("{}", message))
+            .level(Level::Warn)
+            .target("rummy::transport")
+            .module_path(Some("rummy::transport::tcp_server"))
+            .file(Some("src/transport/tcp_server.rs"))
+            .line(Some(42))
+            .key_values(&fields)
+            .build(),
+    )
+}
+
+#[test]
+fn json_line_contains_record_metadata() {
+    let line = LogFormat::Json.format(&capture("say \"hi\"\n"));
+    assert!(line.ends_with("}\n"));
+    assert_eq!(line.matches('\n').count(), 1);
+    assert!(line.contains(r#""level":"WARN""#));
+    assert!(line.contains(r#""target":"rummy::transport""#));
+    assert!(line.contains(r#""module_path":"rummy::transport::tcp_server""#));
+    assert!(line.contains(r#""file":"src/transport/tcp_server.rs:42""#));
+    assert!(line.contains(r#""message":"say \"hi\"\n""#));
+    assert!(line.contains(r#""fields":{"peer":"127.0.0.1:9000","attempt":"2"}"#));
+}
+
+#[test]
+fn text_line_appends_fields() {
+    let line = LogFormat::Text.format(&capture("connected"));
+    assert!(line.ends_with("[WARN] connected peer=127.0.0.1:9000 attempt=2\n"));
+}
+
+#[test]
+fn scoped_context_is_attached_to_records() {
+    let uuid = uuid::Uuid::new_v4();
+    let outer = LogContext::for_connection(uuid, Some("10.0.0.1:4000".parse().unwrap()));
+    let inner = LogContext {
+        request_id: Some(7),
+        ..LogContext::default()
+    };
+    // 内层作用域只设置 request_id，其余字段沿用外层
+    let record = outer.sync_scope(|| inner.sync_scope(|| capture("handled")));
+
+    let text = LogFormat::Text.format(&record);
+    let prefix = format!("[WARN] [uuid={} request_id=7 peer_addr=10.0.0.1:4000] handled", uuid);
+    assert!(text.contains(&prefix));
+    let json = LogFormat::Json.format(&record);
+    assert!(json.contains(&format!(r#""uuid":"{}","request_id":"7","peer_addr":"10.0.0.1:4000""#, uuid)));
+    assert!(LogFormat::Text.format(&capture("plain")).contains("[WARN] plain"));
+}
@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use log::{Level, Record};
+use rummy::logger::{LogCollector, LogFormat, LogRecord, LogSink, RemoteSink, RemoteSinkConfig};
+use rummy::transport::TcpServerTransport;
+
+fn record(message: &str) -> LogRecord {
+    LogRecord::capture(&Record::builder().args(format_args!("{}", message)).level(Level::Info).build())
+}
+
+async fn start_collector(addr: &str, dir: &PathBuf) -> String {
+    let mut transport = TcpServerTransport::new(addr).await.unwrap();
+    let addr = transport.local_addr().to_string();
+    transport.run();
+    let collector = LogCollector::new(transport, dir).unwrap();
+    tokio::spawn(collector.run());
+    addr
+}
+
+fn ship(sink: &mut RemoteSink, messages: &[&str]) {
+    for message in messages {
+        let record = record(message);
+        sink.write(&record, &LogFormat::Text.format(&record)).unwrap();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn collector_writes_one_file_per_agent() {
+    let dir = std::env::temp_dir().join(format!("rummy-collector-{}", uuid::Uuid::new_v4()));
+    let addr = start_collector("127.0.0.1:0", &dir).await;
+
+    let mut config = RemoteSinkConfig::new("edge/01");
+    config.batch_size = 2;
+    let mut sink = RemoteSink::connect(addr, config).unwrap();
+    ship(&mut sink, &["one", "two", "three"]);
+    // 丢弃时发完剩余日志并等待确认
+    tokio::task::spawn_blocking(move || drop(sink)).await.unwrap();
+
+    let contents = std::fs::read_to_string(dir.join("edge_01.log")).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("one") && lines[2].ends_with("three"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn shipper_thread_records_are_not_shipped() {
+    let dir = std::env::temp_dir().join(format!("rummy-collector-{}", uuid::Uuid::new_v4()));
+    let addr = start_collector("127.0.0.1:0", &dir).await;
+    let mut config = RemoteSinkConfig::new("quiet");
+    config.batch_size = 1;
+    let mut sink = RemoteSink::connect(addr, config).unwrap();
+
+    // 转发线程自身（如其中的 TCP 连接）产生的日志不再转发
+    let own = std::thread::Builder::new()
+        .name("rummy-log-shipper".to_string())
+        .spawn(|| record("reconnecting"))
+        .unwrap()
+        .join()
+        .unwrap();
+    sink.write(&own, &LogFormat::Text.format(&own)).unwrap();
+    ship(&mut sink, &["shipped"]);
+    tokio::task::spawn_blocking(move || drop(sink)).await.unwrap();
+
+    let contents = std::fs::read_to_string(dir.join("quiet.log")).unwrap();
+    assert_eq!(contents.lines().count(), 1);
+    assert!(contents.ends_with("shipped\n"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sink_buffers_until_collector_is_reachable() {
+    let dir = std::env::temp_dir().join(format!("rummy-collector-{}", uuid::Uuid::new_v4()));
+    // 先占一个端口再释放，收集端稍后在该端口启动
+    let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+
+    let mut config = RemoteSinkConfig::new("late");
+    config.flush_interval = Duration::from_millis(20);
+    config.reconnect_interval = Duration::from_millis(50);
+    let mut sink = RemoteSink::connect(addr.clone(), config).unwrap();
+    ship(&mut sink, &["buffered"]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    start_collector(&addr, &dir).await;
+    let path = dir.join("late.log");
+    for _ in 0..100 {
+        if std::fs::read_to_string(&path).is_ok_and(|contents| contents.ends_with("buffered\n")) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(std::fs::read_to_string(&path).unwrap().ends_with("buffered\n"));
+    tokio::task::spawn_blocking(move || drop(sink)).await.unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unacked_batches_count_against_buffer() {
+    use rummy::transport::Transport;
+
+    // 只接收不确认的收集端
+    let mut server = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr().to_string();
+    server.run();
+
+    let mut config = RemoteSinkConfig::new("silent");
+    config.batch_size = 1;
+    config.max_buffered = 3;
+    config.flush_interval = Duration::from_millis(20);
+    config.reconnect_interval = Duration::from_millis(20);
+    let mut sink = RemoteSink::connect(addr, config).unwrap();
+    let messages: Vec<String> = (1..=10).map(|i| format!("line {}", i)).collect();
+    ship(&mut sink, &messages.iter().map(String::as_str).collect::<Vec<_>>());
+
+    let mut first = None;
+    for _ in 0..10 {
+        let (uuid, _) = tokio::time::timeout(Duration::from_secs(2), server.receive()).await.unwrap().unwrap();
+        first = Some(uuid);
+    }
+    // 断开后重连，只重发缓存上限内最新的批次
+    server.disconnect(first.unwrap()).await.unwrap();
+    let mut resent = Vec::new();
+    for _ in 0..3 {
+        let (uuid, packet) = tokio::time::timeout(Duration::from_secs(2), server.receive()).await.unwrap().unwrap();
+        assert_ne!(Some(uuid), first);
+        resent.push(String::from_utf8_lossy(&packet.payload).trim_end().to_string());
+    }
+    assert!(resent[0].ends_with("line 8") && resent[2].ends_with("line 10"));
+    let extra = tokio::time::timeout(Duration::from_millis(100), server.receive()).await;
+    assert!(extra.is_err());
+
+    server.close().await.unwrap();
+    tokio::task::spawn_blocking(move || drop(sink)).await.unwrap();
+}
@@ -0,0 +1,55 @@
+#![cfg(unix)]
+
+use log::LevelFilter;
+use rummy::logger::{init_logger_with, LogFormat, LoggerConfig, RingBuffer, SinkConfig, SyslogSink};
+use std::os::unix::net::UnixDatagram;
+
+#[test]
+fn sinks_apply_their_own_level_and_format() {
+    let dir = std::env::temp_dir();
+    let id = uuid::Uuid::new_v4();
+    let path = dir.join(format!("rummy-sink-{}.log", id));
+    let socket_path = dir.join(format!("rummy-syslog-{}.sock", id));
+    let syslog = UnixDatagram::bind(&socket_path).unwrap();
+
+    let ring = RingBuffer::new(2);
+    let mut config = LoggerConfig::new(LevelFilter::Info, &path);
+    config.console.enabled = false;
+    config.sinks.push(SinkConfig {
+        level: LevelFilter::Warn,
+        format: LogFormat::Json,
+        sink: Box::new(ring.sink()),
+    });
+    config.sinks.push(SinkConfig::new(SyslogSink::with_path(&socket_path, "rummy").unwrap()));
+    let guard = init_logger_with(config).expect("Logger init failed");
+
+    log::info!("first");
+    log::warn!("second");
+    log::error!("third");
+    log::warn!("fourth");
+    log::logger().flush();
+
+    // 环形缓冲只保留最近两条 Warn 及以上的日志
+    let lines = ring.last(10);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with('{') && lines[0].contains("\"message\":\"third\""));
+    assert!(lines[1].contains("\"message\":\"fourth\""));
+    assert_eq!(ring.last(1), lines[1..]);
+
+    // 文件与 syslog 收到全部日志
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().count(), 4);
+    let mut buf = [0u8; 1024];
+    let len = syslog.recv(&mut buf).unwrap();
+    let message = String::from_utf8_lossy(&buf[..len]);
+    assert!(message.starts_with("<14>"));
+    assert!(message.contains(" rummy["));
+    // 只有日志内容，不重复时间与级别
+    assert!(message.ends_with("]: first"));
+    let len = syslog.recv(&mut buf).unwrap();
+    assert!(String::from_utf8_lossy(&buf[..len]).starts_with("<12>"));
+
+    drop(guard);
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(socket_path).unwrap();
+}
@@ -0,0 +1,39 @@
+use log::LevelFilter;
+use rummy::logger::{init_logger_with, LoggerConfig, LoggerError};
+
+#[test]
+fn guard_drop_delivers_queued_logs() {
+    let path = std::env::temp_dir().join(format!("rummy-shutdown-{}.log", uuid::Uuid::new_v4()));
+    let rotated = path.with_extension("log.1");
+    let guard = init_logger_with(LoggerConfig::new(LevelFilter::Info, &path)).expect("Logger init failed");
+    let handle = guard.handle();
+
+    for i in 0..1000 {
+        log::info!("Queued message {}", i);
+    }
+    log::logger().flush();
+    let flushed = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(flushed.lines().count(), 1000);
+
+    // 模拟外部 logrotate 移走文件后重新打开
+    std::fs::rename(&path, &rotated).unwrap();
+    handle.reopen().unwrap();
+    handle.set_level(LevelFilter::Warn);
+    log::info!("Filtered message");
+    log::error!("Crash explanation");
+    drop(guard);
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.ends_with("Crash explanation\n"));
+    assert_eq!(contents.lines().count(), 1);
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(rotated).unwrap();
+}
+
+#[test]
+fn unopenable_file_is_reported() {
+    // 目录不存在，初始化返回错误而不是 panic，也不会占用全局 logger
+    let path = std::env::temp_dir().join(format!("rummy-missing-{}", uuid::Uuid::new_v4())).join("app.log");
+    let result = init_logger_with(LoggerConfig::new(LevelFilter::Info, &path));
+    assert!(matches!(result, Err(LoggerError::Io(_))));
+}
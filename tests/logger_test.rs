@@ -4,7 +4,7 @@ use rummy::logger::init_logger;
 
 #[test]
 fn log_test() {
-    init_logger(LevelFilter::Info, "myapp.log").expect("Logger init failed");
+    let _guard = init_logger(LevelFilter::Info, "myapp.log").expect("Logger init failed");
 
     let start = std::time::Instant::now();
 
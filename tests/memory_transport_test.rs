@@ -0,0 +1,51 @@
+use bytes::Bytes;
+use rummy::protocol::{Packet, PacketHeader};
+use rummy::rpc::{RpcClient, RpcServer};
+use rummy::transport::{MemoryClientTransport, MemoryServerTransport, Transport, TransportError};
+
+#[tokio::test]
+async fn rpc_over_memory_transport() {
+    let mut transport = MemoryServerTransport::new();
+    let connector = transport.connector();
+    transport.run();
+    tokio::spawn(RpcServer::with_handler(transport, |_, payload: Bytes| async move {
+        Ok(payload.to_ascii_uppercase())
+    }).serve());
+
+    let first = RpcClient::new(MemoryClientTransport::connect(&connector).await.unwrap());
+    let second = RpcClient::new(MemoryClientTransport::connect(&connector).await.unwrap());
+    assert_eq!(first.call(b"one".to_vec()).await.unwrap(), &b"ONE"[..]);
+    assert_eq!(second.call(b"two".to_vec()).await.unwrap(), &b"TWO"[..]);
+}
+
+#[tokio::test]
+async fn close_semantics_match_tcp() {
+    let mut server = MemoryServerTransport::new();
+    let connector = server.connector();
+    server.run();
+
+    let mut client = MemoryClientTransport::connect(&connector).await.unwrap();
+    let header = PacketHeader::from_payload(b"hello", 0);
+    client.send(uuid::Uuid::nil(), Packet::new(header, &b"hello"[..])).await.unwrap();
+    let (uuid, packet) = server.receive().await.unwrap();
+    assert_eq!(packet.payload, &b"hello"[..]);
+    // 服务端分配的 UUID 与客户端自身的不同，包序号从 1 开始
+    assert_ne!(uuid, client.uuid());
+    assert_eq!(packet.header.sequence, 1);
+
+    // 客户端关闭后服务端移除该连接
+    client.close().await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let header = PacketHeader::from_payload(b"bye", 0);
+    let result = server.send(uuid, Packet::new(header, &b"bye"[..])).await;
+    assert!(matches!(result, Err(TransportError::ConnectionNotFound)));
+
+    // 服务端关闭后已有客户端读到连接结束，新的连接被拒绝
+    let mut other = MemoryClientTransport::connect(&connector).await.unwrap();
+    let header = PacketHeader::from_payload(b"hi", 0);
+    other.send(uuid::Uuid::nil(), Packet::new(header, &b"hi"[..])).await.unwrap();
+    server.receive().await.unwrap();
+    server.close().await.unwrap();
+    assert!(other.receive().await.is_none());
+    assert!(MemoryClientTransport::connect(&connector).await.is_err());
+}
@@ -0,0 +1,50 @@
+use bytes::Bytes;
+use rummy::protocol::{ChecksumAlgo, MsgError, Packet, PacketHeader, HEADER_SIZE};
+
+fn packet(algo: ChecksumAlgo) -> Packet {
+    let payload = b"hello rummy".to_vec();
+    let mut header = PacketHeader::from_payload(&payload, 42);
+    header.checksum_algo = algo;
+    Packet::new(header, payload)
+}
+
+#[test]
+fn roundtrip_with_each_algorithm() {
+    for algo in [ChecksumAlgo::Crc32, ChecksumAlgo::Crc32c, ChecksumAlgo::XxHash32] {
+        let decoded = Packet::from_bytes(&packet(algo).to_bytes()).unwrap();
+        assert_eq!(decoded.header.checksum_algo, algo);
+        assert_eq!(decoded.header.session_id, 42);
+        assert_eq!(decoded.payload, &b"hello rummy"[..]);
+    }
+}
+
+#[test]
+fn header_corruption_is_detected() {
+    for algo in [ChecksumAlgo::Crc32, ChecksumAlgo::Crc32c, ChecksumAlgo::XxHash32] {
+        let mut bytes = packet(algo).to_bytes();
+        // 篡改 session_id
+        bytes[20] ^= 0x01;
+        assert!(matches!(Packet::from_bytes(&bytes), Err(MsgError::ChecksumMismatch)));
+    }
+}
+
+#[test]
+fn v1_checksum_covers_payload_only() {
+    let mut v1 = packet(ChecksumAlgo::Crc32);
+    v1.header.version = 1;
+    let mut bytes = v1.to_bytes();
+    assert!(Packet::from_bytes(&bytes).is_ok());
+    // v1 无法发现包头损坏
+    bytes[20] ^= 0x01;
+    assert!(Packet::from_bytes(&bytes).is_ok());
+}
+
+#[test]
+fn decode_shares_frame_buffer() {
+    let frame = Bytes::from(packet(ChecksumAlgo::Crc32).to_bytes());
+    let decoded = Packet::decode(frame.clone()).unwrap();
+    assert_eq!(decoded.payload.as_ptr(), frame[HEADER_SIZE..].as_ptr());
+    // 克隆只增加引用计数
+    let forwarded = decoded.clone();
+    assert_eq!(forwarded.payload.as_ptr(), decoded.payload.as_ptr());
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+use bytes::Bytes;
+use rummy::protocol::{MsgType, Packet, PacketHeader};
+use rummy::rpc::{RpcClient, RpcError, RpcServer};
+use rummy::transport::{load_certs, load_private_key, QuicClientTransport, QuicServerTransport, Transport};
+use tokio::sync::mpsc;
+
+// 生成自签名证书写入 PEM 文件，再按正常流程加载
+async fn start_server(handler_uuids: mpsc::Sender<uuid::Uuid>) -> (std::net::SocketAddr, Vec<rustls::pki_types::CertificateDer<'static>>) {
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let dir = std::env::temp_dir();
+    let id = uuid::Uuid::new_v4();
+    let cert_path = dir.join(format!("rummy-quic-{}.crt", id));
+    let key_path = dir.join(format!("rummy-quic-{}.key", id));
+    std::fs::write(&cert_path, certified.cert.pem()).unwrap();
+    std::fs::write(&key_path, certified.signing_key.serialize_pem()).unwrap();
+    let certs = load_certs(&cert_path).unwrap();
+    let key = load_private_key(&key_path).unwrap();
+    std::fs::remove_file(cert_path).unwrap();
+    std::fs::remove_file(key_path).unwrap();
+
+    let mut transport = QuicServerTransport::new("127.0.0.1:0".parse().unwrap(), certs.clone(), key).await.unwrap();
+    let addr = transport.local_addr();
+    transport.run();
+    tokio::spawn(RpcServer::with_handler(transport, move |context, payload: Bytes| {
+        let handler_uuids = handler_uuids.clone();
+        async move {
+            let _ = handler_uuids.send(context.uuid()).await;
+            if payload.as_ref() == b"slow" {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+            Ok(payload.to_ascii_uppercase())
+        }
+    }).serve());
+    (addr, certs)
+}
+
+#[tokio::test]
+async fn calls_use_independent_streams() {
+    let (uuid_sender, _uuid_receiver) = mpsc::channel(16);
+    let (addr, certs) = start_server(uuid_sender).await;
+    let client = RpcClient::new(QuicClientTransport::connect(addr, "localhost", certs).await.unwrap());
+
+    // 慢调用超时取消后，同一连接上的其它调用不受影响
+    let slow = client.call_timeout(b"slow".to_vec(), Duration::from_millis(100));
+    let fast = client.call(b"fast".to_vec());
+    let (slow, fast) = tokio::join!(slow, fast);
+    assert!(matches!(slow, Err(RpcError::Timeout)));
+    assert_eq!(fast.unwrap(), &b"FAST"[..]);
+    assert_eq!(client.call(b"again".to_vec()).await.unwrap(), &b"AGAIN"[..]);
+}
+
+async fn call(transport: &mut QuicClientTransport, request_id: u64, payload: &'static [u8]) -> Bytes {
+    let header = PacketHeader::for_request(payload, 0, MsgType::Call, request_id);
+    transport.send(uuid::Uuid::nil(), Packet::new(header, payload)).await.unwrap();
+    let (_, reply) = transport.receive().await.unwrap();
+    assert_eq!(reply.header.request_id, request_id);
+    reply.payload
+}
+
+#[tokio::test]
+async fn migration_keeps_session_uuid() {
+    let (uuid_sender, mut uuid_receiver) = mpsc::channel(16);
+    let (addr, certs) = start_server(uuid_sender).await;
+    let mut transport = QuicClientTransport::connect(addr, "localhost", certs).await.unwrap();
+
+    assert_eq!(call(&mut transport, 1, b"one").await, &b"ONE"[..]);
+    let first = uuid_receiver.recv().await.unwrap();
+
+    // 客户端换到新的本地端口，相当于地址变化
+    transport.rebind(std::net::UdpSocket::bind("127.0.0.1:0").unwrap()).unwrap();
+    assert_eq!(call(&mut transport, 2, b"two").await, &b"TWO"[..]);
+    assert_eq!(uuid_receiver.recv().await.unwrap(), first);
+}
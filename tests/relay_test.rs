@@ -0,0 +1,159 @@
+use std::time::Duration;
+use rummy::protocol::{MsgType, Packet, PacketHeader};
+use rummy::transport::{MemoryClientTransport, MemoryServerTransport, Relay, Transport};
+
+#[cfg(unix)]
+#[tokio::test]
+async fn relays_tcp_to_unix_with_inspection() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use bytes::Bytes;
+    use rummy::rpc::{RpcClient, RpcError, RpcServer};
+    use rummy::transport::{Direction, TcpClientTransport, TcpServerTransport, UnixClientTransport, UnixServerTransport};
+
+    let path = std::env::temp_dir().join(format!("rummy-relay-{}.sock", uuid::Uuid::new_v4()));
+    let mut upstream = UnixServerTransport::new(&path).await.unwrap();
+    upstream.run();
+    tokio::spawn(RpcServer::with_handler(upstream, |_, payload: Bytes| async move {
+        Ok(payload.to_ascii_uppercase())
+    }).serve());
+
+    let mut downstream = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    let addr = downstream.local_addr();
+    downstream.run();
+    let upstream_path = path.clone();
+    let mut relay = Relay::new(downstream, move || UnixClientTransport::connect(upstream_path.clone()));
+    // 拦截内容为 blocked 的调用，并统计回复数
+    let replies = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&replies);
+    relay.set_inspector(move |direction, _, packet: Packet| match direction {
+        Direction::Upstream if packet.header.msg_type == MsgType::Call && packet.payload == b"blocked"[..] => None,
+        Direction::Upstream => Some(packet),
+        Direction::Downstream => {
+            counted.fetch_add(1, Ordering::Relaxed);
+            Some(packet)
+        }
+    });
+    tokio::spawn(relay.run());
+
+    let client = RpcClient::new(TcpClientTransport::connect(addr).await.unwrap());
+    assert_eq!(client.call(b"relayed".to_vec()).await.unwrap(), &b"RELAYED"[..]);
+    let blocked = client.call_timeout(b"blocked".to_vec(), Duration::from_millis(100)).await;
+    assert!(matches!(blocked, Err(RpcError::Timeout)));
+    assert_eq!(replies.load(Ordering::Relaxed), 1);
+    std::fs::remove_file(path).unwrap();
+}
+
+fn hello() -> Packet {
+    let header = PacketHeader::for_request(b"hello", 0, MsgType::Call, 1);
+    Packet::new(header, &b"hello"[..])
+}
+
+#[tokio::test]
+async fn closes_the_other_side() {
+    let mut upstream = MemoryServerTransport::new();
+    let mut upstream_closed = upstream.closed_connections().unwrap();
+    let upstream_connector = upstream.connector();
+    upstream.run();
+
+    let mut downstream = MemoryServerTransport::new();
+    let downstream_connector = downstream.connector();
+    downstream.run();
+    tokio::spawn(Relay::new(downstream, move || {
+        let connector = upstream_connector.clone();
+        async move { MemoryClientTransport::connect(&connector).await }
+    }).run());
+
+    // 下游断开后上游连接随之关闭
+    let mut first = MemoryClientTransport::connect(&downstream_connector).await.unwrap();
+    first.send(uuid::Uuid::nil(), hello()).await.unwrap();
+    let (session, packet) = upstream.receive().await.unwrap();
+    assert_eq!(packet.payload, &b"hello"[..]);
+    let header = PacketHeader::for_request(b"world", 0, MsgType::Reply, 1);
+    upstream.send(session, Packet::new(header, &b"world"[..])).await.unwrap();
+    assert_eq!(first.receive().await.unwrap().1.payload, &b"world"[..]);
+    first.close().await.unwrap();
+    let closed = tokio::time::timeout(Duration::from_secs(1), upstream_closed.recv()).await.unwrap();
+    assert_eq!(closed, Some(session));
+
+    // 上游断开后下游连接随之关闭
+    let mut second = MemoryClientTransport::connect(&downstream_connector).await.unwrap();
+    second.send(uuid::Uuid::nil(), hello()).await.unwrap();
+    let (session, _) = upstream.receive().await.unwrap();
+    upstream.disconnect(session).await.unwrap();
+    let ended = tokio::time::timeout(Duration::from_secs(1), second.receive()).await.unwrap();
+    assert!(ended.is_none());
+
+    // 下游连接已停止读取，之后再发送不会打开新的上游会话
+    let _ = second.send(uuid::Uuid::nil(), hello()).await;
+    let reopened = tokio::time::timeout(Duration::from_millis(200), upstream.receive()).await;
+    assert!(reopened.is_err());
+}
+
+#[tokio::test]
+async fn slow_downstream_does_not_stall_others() {
+    let mut upstream = MemoryServerTransport::new();
+    let upstream_connector = upstream.connector();
+    upstream.run();
+
+    let mut downstream = MemoryServerTransport::new();
+    let downstream_connector = downstream.connector();
+    downstream.run();
+    tokio::spawn(Relay::new(downstream, move || {
+        let connector = upstream_connector.clone();
+        async move { MemoryClientTransport::connect(&connector).await }
+    }).run());
+
+    // slow 从不读取回复，上游向它推送的包很快填满下游的写入队列，积压过多后会话被断开
+    let slow = MemoryClientTransport::connect(&downstream_connector).await.unwrap();
+    slow.send(uuid::Uuid::nil(), hello()).await.unwrap();
+    let (slow_session, _) = upstream.receive().await.unwrap();
+    for _ in 0..500 {
+        let header = PacketHeader::for_request(b"flood", 0, MsgType::Reply, 1);
+        if upstream.send(slow_session, Packet::new(header, &b"flood"[..])).await.is_err() {
+            break;
+        }
+    }
+
+    // 另一个客户端的往返不受影响，也不用等 slow 的回复超时
+    let mut fast = MemoryClientTransport::connect(&downstream_connector).await.unwrap();
+    fast.send(uuid::Uuid::nil(), hello()).await.unwrap();
+    let (session, _) = tokio::time::timeout(Duration::from_millis(200), upstream.receive()).await.unwrap().unwrap();
+    let header = PacketHeader::for_request(b"world", 0, MsgType::Reply, 1);
+    upstream.send(session, Packet::new(header, &b"world"[..])).await.unwrap();
+    let reply = tokio::time::timeout(Duration::from_millis(200), fast.receive()).await.unwrap().unwrap();
+    assert_eq!(reply.1.payload, &b"world"[..]);
+}
+
+#[tokio::test]
+async fn shutdown_ends_run() {
+    use rummy::transport::{TcpClientTransport, TcpServerTransport};
+
+    let mut upstream = MemoryServerTransport::new();
+    let mut upstream_closed = upstream.closed_connections().unwrap();
+    let upstream_connector = upstream.connector();
+    upstream.run();
+
+    // TCP 下游不会自行结束，只能通过 shutdown 让 run 返回
+    let mut downstream = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    let addr = downstream.local_addr();
+    downstream.run();
+    let relay = Relay::new(downstream, move || {
+        let connector = upstream_connector.clone();
+        async move { MemoryClientTransport::connect(&connector).await }
+    });
+    let shutdown = relay.shutdown_handle();
+    let running = tokio::spawn(relay.run());
+
+    let mut client = TcpClientTransport::connect(addr).await.unwrap();
+    client.send(uuid::Uuid::nil(), hello()).await.unwrap();
+    let (session, _) = upstream.receive().await.unwrap();
+
+    shutdown.shutdown();
+    let result = tokio::time::timeout(Duration::from_secs(2), running).await.unwrap().unwrap();
+    assert!(result.is_ok());
+    let closed = tokio::time::timeout(Duration::from_secs(1), upstream_closed.recv()).await.unwrap();
+    assert_eq!(closed, Some(session));
+    let ended = tokio::time::timeout(Duration::from_secs(1), client.receive()).await.unwrap();
+    assert!(ended.is_none());
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+use rummy::protocol::{MsgError, Packet, PacketHeader, ReplayConfig, ReplayGuard};
+use rummy::transport::{TcpServerTransport, Transport};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use uuid::Uuid;
+
+fn header(session_id: u64, sequence: u64) -> PacketHeader {
+    let mut header = PacketHeader::from_payload(b"payload", session_id);
+    header.sequence = sequence;
+    header
+}
+
+#[test]
+fn rejects_stale_timestamp() {
+    let mut guard = ReplayGuard::new(ReplayConfig {
+        max_skew: Some(Duration::from_secs(5)),
+        check_sequence: false,
+    });
+    let connection = Uuid::new_v4();
+    assert!(guard.check(connection, &header(0, 0)).is_ok());
+
+    let mut stale = header(0, 0);
+    stale.timestamp -= 60_000;
+    assert!(matches!(guard.check(connection, &stale), Err(MsgError::StalePacket)));
+}
+
+#[test]
+fn rejects_replayed_sequence() {
+    let mut guard = ReplayGuard::new(ReplayConfig {
+        max_skew: None,
+        check_sequence: true,
+    });
+    let connection = Uuid::new_v4();
+    assert!(guard.check(connection, &header(0, 1)).is_ok());
+    assert!(guard.check(connection, &header(0, 3)).is_ok());
+    // 窗口内乱序到达的包仍然接受
+    assert!(guard.check(connection, &header(0, 2)).is_ok());
+    // 未认证会话同样检查连接内的序号
+    assert!(matches!(guard.check(connection, &header(0, 3)), Err(MsgError::ReplayedPacket)));
+    assert!(matches!(guard.check(connection, &header(0, 0)), Err(MsgError::ReplayedPacket)));
+    // 落在窗口之外的旧序号被拒绝
+    assert!(guard.check(connection, &header(0, 200)).is_ok());
+    assert!(matches!(guard.check(connection, &header(0, 100)), Err(MsgError::ReplayedPacket)));
+    // 不同连接的序号互不影响
+    assert!(guard.check(Uuid::new_v4(), &header(0, 1)).is_ok());
+}
+
+#[test]
+fn reconnect_starts_a_new_window() {
+    let mut guard = ReplayGuard::new(ReplayConfig::default());
+    let first = Uuid::new_v4();
+    assert!(guard.check(first, &header(7, 1)).is_ok());
+    assert!(guard.check(first, &header(7, 2)).is_ok());
+    guard.remove(first);
+
+    // 同一会话换一条连接后序号从 1 重新开始
+    let mut resent = header(7, 1);
+    resent.timestamp += 1;
+    assert!(guard.check(Uuid::new_v4(), &resent).is_ok());
+}
+
+#[tokio::test]
+async fn rejects_replay_over_another_connection() {
+    let mut server = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr();
+    server.set_replay_protection(ReplayConfig::default());
+    server.run();
+
+    // 直接写入编码后的字节，模拟截获并原样重放
+    let captured = Packet::new(header(9, 1), &b"payload"[..]).to_bytes();
+    let mut first = TcpStream::connect(addr).await.unwrap();
+    first.write_all(&captured).await.unwrap();
+    let (_, packet) = server.receive().await.unwrap();
+    assert_eq!(packet.header.sequence, 1);
+
+    let mut second = TcpStream::connect(addr).await.unwrap();
+    second.write_all(&captured).await.unwrap();
+    let replayed = tokio::time::timeout(Duration::from_millis(200), server.receive()).await;
+    assert!(replayed.is_err());
+
+    // 新连接上的新包从序号 1 开始仍然接受
+    let mut fresh = header(9, 1);
+    fresh.timestamp += 1;
+    second.write_all(&Packet::new(fresh, &b"payload"[..]).to_bytes()).await.unwrap();
+    let (_, packet) = server.receive().await.unwrap();
+    assert_eq!(packet.header.sequence, 1);
+}
+
+#[tokio::test]
+async fn reconnecting_client_is_accepted() {
+    use bytes::Bytes;
+    use rummy::rpc::{RpcClient, RpcServer};
+    use rummy::transport::TcpClientTransport;
+
+    let mut server = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    let addr = server.local_addr();
+    server.set_replay_protection(ReplayConfig::default());
+    server.run();
+    tokio::spawn(RpcServer::with_handler(server, |_, payload: Bytes| async move { Ok(payload.to_vec()) }).serve());
+
+    // 每条新连接的序号都从 1 开始
+    for _ in 0..2 {
+        let client = RpcClient::new(TcpClientTransport::connect(addr).await.unwrap());
+        for _ in 0..3 {
+            assert_eq!(client.call(b"ping".to_vec()).await.unwrap(), &b"ping"[..]);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::PathBuf;
+use rummy::logger::{RotatingFile, Rotation, RotationNaming};
+
+fn temp_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rummy-rotation-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn file_names(dir: &PathBuf) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn numbered_rotation_keeps_max_files() {
+    let dir = temp_dir();
+    let rotation = Rotation {
+        max_size: Some(20),
+        max_files: Some(2),
+        ..Rotation::default()
+    };
+    let mut file = RotatingFile::open(dir.join("app.log"), rotation).unwrap();
+    for i in 0..5 {
+        file.write_line(format!("line {:02} xxxxxxxx\n", i).as_bytes()).unwrap();
+    }
+    file.flush().unwrap();
+
+    assert_eq!(file_names(&dir), vec!["app.log", "app.log.1", "app.log.2"]);
+    assert_eq!(fs::read_to_string(dir.join("app.log")).unwrap(), "line 04 xxxxxxxx\n");
+    assert_eq!(fs::read_to_string(dir.join("app.log.1")).unwrap(), "line 03 xxxxxxxx\n");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn dated_rotation_compresses_old_files() {
+    let dir = temp_dir();
+    let rotation = Rotation {
+        max_size: Some(20),
+        naming: RotationNaming::Dated,
+        max_files: Some(3),
+        compress: true,
+        ..Rotation::default()
+    };
+    let mut file = RotatingFile::open(dir.join("app.log"), rotation).unwrap();
+    for i in 0..6 {
+        file.write_line(format!("line {:02} xxxxxxxx\n", i).as_bytes()).unwrap();
+    }
+
+    let names = file_names(&dir);
+    assert_eq!(names.len(), 4);
+    assert!(names.iter().filter(|name| *name != "app.log").all(|name| name.ends_with(".gz")));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn retention_leaves_unrelated_files_alone() {
+    let dir = temp_dir();
+    for name in ["app.log.bak", "app.log.gz", "app.log.old.1"] {
+        fs::write(dir.join(name), "keep").unwrap();
+    }
+    let rotation = Rotation {
+        max_size: Some(20),
+        naming: RotationNaming::Dated,
+        max_files: Some(1),
+        ..Rotation::default()
+    };
+    let mut file = RotatingFile::open(dir.join("app.log"), rotation).unwrap();
+    for i in 0..4 {
+        file.write_line(format!("line {:02} xxxxxxxx\n", i).as_bytes()).unwrap();
+    }
+
+    let names = file_names(&dir);
+    assert_eq!(names.len(), 5);
+    for name in ["app.log.bak", "app.log.gz", "app.log.old.1"] {
+        assert!(names.iter().any(|existing| existing == name));
+    }
+
+    let zero = Rotation {
+        max_files: Some(0),
+        ..Rotation::default()
+    };
+    assert!(RotatingFile::open(dir.join("other.log"), zero).is_err());
+    fs::remove_dir_all(dir).unwrap();
+}
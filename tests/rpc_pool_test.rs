@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use rummy::protocol::{MsgType, Packet, PacketHeader};
+use rummy::rpc::{Balance, PoolConfig, RpcPool};
+use rummy::transport::{MemoryClientTransport, MemoryConnector, MemoryServerTransport, Transport, TransportError};
+use tokio::sync::oneshot;
+
+mod common;
+use common::{start_named, start_slow};
+
+#[tokio::test]
+async fn balances_across_endpoints() {
+    let endpoints = vec![start_named("a").await, start_named("b").await, start_named("c").await];
+    let pool = RpcPool::new(endpoints.clone(), PoolConfig::default()).await;
+    assert_eq!(pool.healthy_endpoints(), endpoints);
+
+    let mut counts = HashMap::new();
+    for _ in 0..6 {
+        *counts.entry(pool.call(Vec::new()).await.unwrap()).or_insert(0) += 1;
+    }
+    assert_eq!(counts.len(), 3);
+    assert!(counts.values().all(|&count| count == 2));
+
+    // 同一个键总是落到同一个端点
+    let config = PoolConfig { balance: Balance::ConsistentHash, ..PoolConfig::default() };
+    let pool = RpcPool::new(endpoints, config).await;
+    let first = pool.call_with_key(b"user-42", Vec::new()).await.unwrap();
+    for _ in 0..5 {
+        assert_eq!(pool.call_with_key(b"user-42", Vec::new()).await.unwrap(), first);
+    }
+}
+
+// 手动驱动的内存服务端，收到关闭信号后关闭传输
+fn start_closable(mut server: MemoryServerTransport, name: &'static str) -> oneshot::Sender<()> {
+    let (close_sender, mut close_receiver) = oneshot::channel();
+    server.run();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                incoming = server.receive() => {
+                    let Some((uuid, packet)) = incoming else { break };
+                    let header = PacketHeader::for_request(name.as_bytes(), 0, MsgType::Reply, packet.header.request_id);
+                    let _ = server.send(uuid, Packet::new(header, name.as_bytes())).await;
+                }
+                _ = &mut close_receiver => {
+                    server.close().await.unwrap();
+                    break;
+                }
+            }
+        }
+    });
+    close_sender
+}
+
+#[tokio::test]
+async fn ejects_and_reprobes_endpoints() {
+    let servers: Arc<Mutex<HashMap<String, MemoryConnector>>> = Arc::new(Mutex::new(HashMap::new()));
+    let a = MemoryServerTransport::new();
+    servers.lock().unwrap().insert("a".to_string(), a.connector());
+    let close_a = start_closable(a, "a");
+
+    let registry = Arc::clone(&servers);
+    let config = PoolConfig { probe_interval: Duration::from_millis(50), ..PoolConfig::default() };
+    let pool = RpcPool::with_connector(vec!["a".to_string(), "b".to_string()], config, move |addr| {
+        let connector = registry.lock().unwrap().get(&addr).cloned();
+        async move {
+            match connector {
+                Some(connector) => MemoryClientTransport::connect(&connector).await,
+                None => Err(TransportError::ConnectionNotFound),
+            }
+        }
+    })
+    .await;
+    // b 还没有启动，先被摘除
+    assert_eq!(pool.healthy_endpoints(), vec!["a".to_string()]);
+
+    // b 启动后由后台重连恢复
+    let b = MemoryServerTransport::new();
+    servers.lock().unwrap().insert("b".to_string(), b.connector());
+    let _close_b = start_closable(b, "b");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(pool.healthy_endpoints().len(), 2);
+
+    // a 关闭后第一次失败的调用将其摘除，之后的调用都落到 b
+    close_a.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    for _ in 0..4 {
+        let _ = pool.call(Vec::new()).await;
+    }
+    assert_eq!(pool.healthy_endpoints(), vec!["b".to_string()]);
+    for _ in 0..4 {
+        assert_eq!(pool.call(Vec::new()).await.unwrap(), &b"b"[..]);
+    }
+}
+
+#[tokio::test]
+async fn least_outstanding_avoids_busy_endpoint() {
+    let endpoints = vec![start_slow("a").await, start_slow("b").await];
+    let config = PoolConfig { balance: Balance::LeastOutstanding, ..PoolConfig::default() };
+    let pool = Arc::new(RpcPool::new(endpoints, config).await);
+
+    // 一个端点有调用进行中时，其余调用都落到另一个端点
+    let slow = tokio::spawn({
+        let pool = Arc::clone(&pool);
+        async move { pool.call(b"slow".to_vec()).await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let mut fast = Vec::new();
+    for _ in 0..4 {
+        fast.push(pool.call(Vec::new()).await.unwrap());
+    }
+    assert!(fast.iter().all(|name| *name == fast[0]));
+    assert_ne!(slow.await.unwrap(), fast[0]);
+
+    // 调用方放弃的调用不会让计数一直偏高
+    let abandoned = tokio::time::timeout(Duration::from_millis(20), pool.call(b"slow".to_vec())).await;
+    assert!(abandoned.is_err());
+    let mut names = std::collections::HashSet::new();
+    for _ in 0..4 {
+        names.insert(pool.call(Vec::new()).await.unwrap());
+    }
+    assert_eq!(names.len(), 2);
+}
+
+#[tokio::test]
+async fn unreachable_endpoints_are_probed_concurrently() {
+    let server = MemoryServerTransport::new();
+    let connector = server.connector();
+    let _close = start_closable(server, "ok");
+
+    // 两个永远连不上的端点，逐个等待连接超时会超过 400ms
+    let config = PoolConfig { connect_timeout: Duration::from_millis(200), ..PoolConfig::default() };
+    let endpoints = vec!["hang-1".to_string(), "hang-2".to_string(), "ok".to_string()];
+    let started = std::time::Instant::now();
+    let pool = RpcPool::with_connector(endpoints, config, move |addr| {
+        let connector = connector.clone();
+        async move {
+            if addr.starts_with("hang") {
+                std::future::pending::<()>().await;
+            }
+            MemoryClientTransport::connect(&connector).await
+        }
+    })
+    .await;
+    assert!(started.elapsed() < Duration::from_millis(350));
+    assert_eq!(pool.healthy_endpoints(), vec!["ok".to_string()]);
+}
+
+#[tokio::test]
+async fn concurrent_updates_connect_once() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let server = MemoryServerTransport::new();
+    let connector = server.connector();
+    let _close = start_closable(server, "a");
+
+    let connects = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&connects);
+    let pool = RpcPool::with_connector(Vec::new(), PoolConfig::default(), move |_| {
+        let connector = connector.clone();
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            MemoryClientTransport::connect(&connector).await
+        }
+    })
+    .await;
+
+    // 第一次连接尚未完成时再次更新，不会重复连接同一端点
+    let endpoints = vec!["a".to_string()];
+    tokio::join!(pool.set_endpoints(endpoints.clone()), pool.set_endpoints(endpoints.clone()));
+    assert_eq!(connects.load(Ordering::SeqCst), 1);
+    assert_eq!(pool.healthy_endpoints(), endpoints);
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+use bytes::Bytes;
+use rummy::rpc::{RpcClient, RpcError};
+use rummy::transport::TcpClientTransport;
+use tokio::sync::mpsc;
+
+mod common;
+use common::start_server;
+
+#[tokio::test]
+async fn call_roundtrip() {
+    let addr = start_server(|_, payload: Bytes| async move {
+        if payload.is_empty() {
+            Err(b"empty".to_vec())
+        } else {
+            Ok(payload.iter().rev().copied().collect())
+        }
+    })
+    .await;
+    let client = RpcClient::new(TcpClientTransport::connect(addr).await.unwrap());
+
+    assert_eq!(client.call(b"abc".to_vec()).await.unwrap(), &b"cba"[..]);
+    match client.call(Vec::new()).await {
+        Err(RpcError::Remote(payload)) => assert_eq!(payload, &b"empty"[..]),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn timeout_cancels_handler() {
+    let (observed_sender, mut observed_receiver) = mpsc::channel(1);
+    let addr = start_server(move |context, _| {
+        let observed_sender = observed_sender.clone();
+        async move {
+            context.cancelled().await;
+            let _ = observed_sender.send(context.request_id()).await;
+            Ok(Vec::new())
+        }
+    })
+    .await;
+    let client = RpcClient::new(TcpClientTransport::connect(addr).await.unwrap());
+
+    let result = client.call_timeout(b"slow".to_vec(), Duration::from_millis(50)).await;
+    assert!(matches!(result, Err(RpcError::Timeout)));
+
+    let request_id = tokio::time::timeout(Duration::from_secs(2), observed_receiver.recv())
+        .await
+        .expect("handler did not observe cancellation");
+    assert_eq!(request_id, Some(1));
+}
+
+#[tokio::test]
+async fn deadline_reaches_handler() {
+    let addr = start_server(|context, _| async move {
+        match context.remaining() {
+            Some(remaining) => Ok((remaining.as_millis() as u64).to_le_bytes().to_vec()),
+            None => Err(b"no deadline".to_vec()),
+        }
+    })
+    .await;
+    let client = RpcClient::new(TcpClientTransport::connect(addr).await.unwrap());
+
+    let reply = client.call_timeout(Vec::new(), Duration::from_secs(5)).await.unwrap();
+    let remaining = u64::from_le_bytes(reply[..].try_into().unwrap());
+    assert!(remaining > 0 && remaining <= 5000);
+
+    assert!(matches!(client.call(Vec::new()).await, Err(RpcError::Remote(_))));
+}
+
+#[tokio::test]
+async fn expired_call_is_rejected_before_dispatch() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use rummy::protocol::{now_millis, MsgType, Packet, PacketHeader};
+    use rummy::transport::Transport;
+
+    let dispatched = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&dispatched);
+    let addr = start_server(move |_, _| {
+        counted.fetch_add(1, Ordering::Relaxed);
+        async { Ok(Vec::new()) }
+    })
+    .await;
+
+    // RpcClient 本地就会拒绝已过期的调用，这里直接发送原始包
+    let mut transport = TcpClientTransport::connect(addr).await.unwrap();
+    let mut header = PacketHeader::for_request(b"late", 0, MsgType::Call, 7);
+    header.deadline = now_millis() - 1000;
+    transport.send(uuid::Uuid::nil(), Packet::new(header, &b"late"[..])).await.unwrap();
+
+    let (_, reply) = tokio::time::timeout(Duration::from_secs(1), transport.receive()).await.unwrap().unwrap();
+    assert_eq!(reply.header.msg_type, MsgType::DeadlineExceeded);
+    assert_eq!(reply.header.request_id, 7);
+    assert_eq!(dispatched.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn handler_error_is_not_mistaken_for_timeout() {
+    let addr = start_server(|_, _| async { Err(b"deadline exceeded".to_vec()) }).await;
+    let client = RpcClient::new(TcpClientTransport::connect(addr).await.unwrap());
+    match client.call_timeout(Vec::new(), Duration::from_secs(5)).await {
+        Err(RpcError::Remote(payload)) => assert_eq!(payload, &b"deadline exceeded"[..]),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
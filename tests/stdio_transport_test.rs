@@ -0,0 +1,39 @@
+use bytes::Bytes;
+use rummy::protocol::{Packet, PacketHeader};
+use rummy::rpc::{RpcClient, RpcServer};
+use rummy::transport::{StdioTransport, Transport};
+use tokio::process::Command;
+
+#[tokio::test]
+async fn rpc_over_reader_writer_pair() {
+    // 两条单向管道模拟子进程的 stdin 与 stdout
+    let (client_stream, server_stream) = tokio::io::duplex(4096);
+    let (server_reader, server_writer) = tokio::io::split(server_stream);
+    let (client_reader, client_writer) = tokio::io::split(client_stream);
+
+    let server = StdioTransport::new(server_reader, server_writer);
+    tokio::spawn(RpcServer::with_handler(server, |_, payload: Bytes| async move {
+        Ok(payload.to_ascii_uppercase())
+    }).serve());
+
+    let client = RpcClient::new(StdioTransport::new(client_reader, client_writer));
+    assert_eq!(client.call(b"stdio".to_vec()).await.unwrap(), &b"STDIO"[..]);
+}
+
+#[tokio::test]
+async fn spawned_child_sees_framed_packets() {
+    // cat 把收到的帧原样写回
+    let mut transport = StdioTransport::spawn(Command::new("cat")).unwrap();
+    assert!(transport.child_id().is_some());
+
+    let header = PacketHeader::from_payload(b"echo", 0);
+    transport.send(uuid::Uuid::nil(), Packet::new(header, &b"echo"[..])).await.unwrap();
+    let (uuid, packet) = transport.receive().await.unwrap();
+    assert_eq!(uuid, transport.uuid());
+    assert_eq!(packet.payload, &b"echo"[..]);
+    assert_eq!(packet.header.sequence, 1);
+
+    // 关闭 stdin 后 cat 自行退出
+    transport.close().await.unwrap();
+    assert!(transport.child_id().is_none());
+}
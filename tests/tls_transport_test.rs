@@ -0,0 +1,81 @@
+use bytes::Bytes;
+use rcgen::{BasicConstraints, CertificateParams, CertifiedIssuer, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair};
+use rummy::protocol::{Packet, PacketHeader};
+use rummy::rpc::{RpcClient, RpcServer};
+use rummy::transport::{TlsClientTransport, TlsServerTransport, Transport};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+struct Certs {
+    ca: CertificateDer<'static>,
+    server: (CertificateDer<'static>, PrivateKeyDer<'static>),
+    client: (CertificateDer<'static>, PrivateKeyDer<'static>),
+}
+
+// 一个 CA 同时签发服务端与客户端证书
+fn generate() -> Certs {
+    let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    ca_params.distinguished_name.push(DnType::CommonName, "rummy test ca");
+    let ca = CertifiedIssuer::self_signed(ca_params, KeyPair::generate().unwrap()).unwrap();
+
+    let sign = |params: CertificateParams| {
+        let key = KeyPair::generate().unwrap();
+        let cert = params.signed_by(&key, &ca).unwrap();
+        (cert.der().clone(), PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key.serialize_der())))
+    };
+    let server = sign(CertificateParams::new(vec!["localhost".to_string()]).unwrap());
+    let mut client_params = CertificateParams::new(vec!["client.rummy.test".to_string()]).unwrap();
+    client_params.distinguished_name.push(DnType::CommonName, "client-1");
+    client_params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+    let client = sign(client_params);
+
+    Certs { ca: ca.der().clone(), server, client }
+}
+
+#[tokio::test]
+async fn rpc_over_tls() {
+    let certs = generate();
+    let (cert, key) = certs.server;
+    let mut transport = TlsServerTransport::new("127.0.0.1:0", vec![cert], key).await.unwrap();
+    let addr = transport.local_addr();
+    transport.run();
+    tokio::spawn(RpcServer::with_handler(transport, |_, payload: Bytes| async move {
+        Ok(payload.to_ascii_uppercase())
+    }).serve());
+
+    let client = RpcClient::new(TlsClientTransport::connect(addr, "localhost", vec![certs.ca.clone()]).await.unwrap());
+    assert_eq!(client.call(b"tls".to_vec()).await.unwrap(), &b"TLS"[..]);
+
+    // 服务端名称与证书不符时握手失败
+    assert!(TlsClientTransport::connect(addr, "example.com", vec![certs.ca]).await.is_err());
+}
+
+#[tokio::test]
+async fn mutual_tls_exposes_client_identity() {
+    let certs = generate();
+    let (cert, key) = certs.server;
+    let mut server = TlsServerTransport::new("127.0.0.1:0", vec![cert], key).await.unwrap();
+    server.require_client_auth(vec![certs.ca.clone()]).unwrap();
+    let addr = server.local_addr();
+    server.run();
+
+    // 未出示证书的客户端在第一次读写时被拒绝
+    let mut anonymous = TlsClientTransport::connect(addr, "localhost", vec![certs.ca.clone()]).await.unwrap();
+    let header = PacketHeader::from_payload(b"anon", 0);
+    let _ = anonymous.send(uuid::Uuid::nil(), Packet::new(header, &b"anon"[..])).await;
+    assert!(anonymous.receive().await.is_none());
+
+    let (cert, key) = certs.client;
+    let client = TlsClientTransport::connect_with_client_cert(addr, "localhost", vec![certs.ca], vec![cert], key)
+        .await
+        .unwrap();
+    let header = PacketHeader::from_payload(b"hello", 0);
+    client.send(uuid::Uuid::nil(), Packet::new(header, &b"hello"[..])).await.unwrap();
+    let (uuid, packet) = server.receive().await.unwrap();
+    assert_eq!(packet.payload, &b"hello"[..]);
+
+    let identity = server.connection_info(uuid).await.unwrap().peer_identity.unwrap();
+    assert_eq!(identity.common_name.as_deref(), Some("client-1"));
+    assert_eq!(identity.dns_names, vec!["client.rummy.test".to_string()]);
+    assert!(identity.subject.contains("CN=client-1"));
+}
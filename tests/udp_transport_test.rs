@@ -0,0 +1,181 @@
+use std::time::Duration;
+use bytes::Bytes;
+use rummy::protocol::{Packet, PacketHeader};
+use rummy::rpc::{RpcClient, RpcServer};
+use rummy::transport::{Transport, UdpClientTransport, UdpConfig, UdpServerTransport};
+use tokio::net::UdpSocket;
+
+fn reliable() -> UdpConfig {
+    UdpConfig {
+        reliable: true,
+        retransmit_interval: Duration::from_millis(20),
+        ..UdpConfig::default()
+    }
+}
+
+// 类型、序号与二者的 CRC32
+fn prefix(kind: u8, sequence: u64) -> Vec<u8> {
+    let mut prefix = vec![kind];
+    prefix.extend_from_slice(&sequence.to_le_bytes());
+    let checksum = crc32fast::hash(&prefix);
+    prefix.extend_from_slice(&checksum.to_le_bytes());
+    prefix
+}
+
+#[tokio::test]
+async fn rpc_over_udp() {
+    let mut transport = UdpServerTransport::new("127.0.0.1:0", reliable()).await.unwrap();
+    let addr = transport.local_addr();
+    transport.run();
+    tokio::spawn(RpcServer::with_handler(transport, |_, payload: Bytes| async move {
+        Ok(payload.iter().rev().copied().collect())
+    }).serve());
+
+    let client = RpcClient::new(UdpClientTransport::connect(addr, reliable()).await.unwrap());
+    assert_eq!(client.call(b"abc".to_vec()).await.unwrap(), &b"cba"[..]);
+}
+
+#[tokio::test]
+async fn unacked_datagrams_are_retransmitted_and_deduplicated() {
+    // 用裸 socket 扮演对端：不回复第一次收到的数据，等待重传
+    let peer = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let mut client = UdpClientTransport::connect(peer.local_addr().unwrap(), reliable()).await.unwrap();
+    let header = PacketHeader::from_payload(b"hello", 0);
+    client.send(uuid::Uuid::nil(), Packet::new(header, &b"hello"[..])).await.unwrap();
+
+    let mut first = [0u8; 256];
+    let (len, client_addr) = peer.recv_from(&mut first).await.unwrap();
+    let mut retransmitted = [0u8; 256];
+    let (retransmitted_len, _) = peer.recv_from(&mut retransmitted).await.unwrap();
+    assert_eq!(first[..len], retransmitted[..retransmitted_len]);
+    assert_eq!(first[0], 1);
+
+    // 回复 ACK 后不再重传
+    let sequence = u64::from_le_bytes(first[1..9].try_into().unwrap());
+    peer.send_to(&prefix(2, sequence), client_addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    while peer.try_recv_from(&mut retransmitted).is_ok() {}
+    let quiet = tokio::time::timeout(Duration::from_millis(100), peer.recv_from(&mut retransmitted)).await;
+    assert!(quiet.is_err());
+
+    // 同一数据报发两次，客户端只交付一次
+    peer.send_to(&first[..len], client_addr).await.unwrap();
+    peer.send_to(&first[..len], client_addr).await.unwrap();
+    let (_, packet) = client.receive().await.unwrap();
+    assert_eq!(packet.payload, &b"hello"[..]);
+    let duplicate = tokio::time::timeout(Duration::from_millis(100), client.receive()).await;
+    assert!(duplicate.is_err());
+}
+
+#[tokio::test]
+async fn corrupted_reliable_datagram_is_not_acked() {
+    let peer = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    // 客户端自身发送不要求确认，先发一个包让对端得知其地址
+    let mut client = UdpClientTransport::connect(peer.local_addr().unwrap(), UdpConfig::default()).await.unwrap();
+    let hello = PacketHeader::from_payload(b"hi", 0);
+    client.send(uuid::Uuid::nil(), Packet::new(hello, &b"hi"[..])).await.unwrap();
+    let mut buf = [0u8; 256];
+    let (_, client_addr) = peer.recv_from(&mut buf).await.unwrap();
+
+    let mut header = PacketHeader::from_payload(b"hello", 0);
+    header.sequence = 1;
+    let mut datagram = prefix(1, 1);
+    datagram.extend_from_slice(&Packet::new(header, &b"hello"[..]).to_bytes());
+    let mut corrupted = datagram.clone();
+    *corrupted.last_mut().unwrap() ^= 0xff;
+
+    // 内容损坏或前缀损坏时既不确认也不交付
+    let mut bad_prefix = datagram.clone();
+    bad_prefix[3] ^= 0xff;
+    peer.send_to(&corrupted, client_addr).await.unwrap();
+    peer.send_to(&bad_prefix, client_addr).await.unwrap();
+    let ack = tokio::time::timeout(Duration::from_millis(100), peer.recv_from(&mut buf)).await;
+    assert!(ack.is_err());
+    let delivered = tokio::time::timeout(Duration::from_millis(50), client.receive()).await;
+    assert!(delivered.is_err());
+
+    // 对端重传完好的数据报后正常确认与交付
+    peer.send_to(&datagram, client_addr).await.unwrap();
+    let (len, _) = peer.recv_from(&mut buf).await.unwrap();
+    assert_eq!(buf[..len], prefix(2, 1)[..]);
+    let (_, packet) = client.receive().await.unwrap();
+    assert_eq!(packet.payload, &b"hello"[..]);
+}
+
+fn datagram(kind: u8, sequence: u64, payload: &[u8]) -> Vec<u8> {
+    let mut header = PacketHeader::from_payload(payload, 0);
+    header.sequence = sequence;
+    let mut datagram = prefix(kind, sequence);
+    datagram.extend_from_slice(&Packet::new(header, payload.to_vec()).to_bytes());
+    datagram
+}
+
+#[tokio::test]
+async fn new_peers_beyond_limit_are_ignored() {
+    let config = UdpConfig { max_peers: 1, ..UdpConfig::default() };
+    let mut server = UdpServerTransport::new("127.0.0.1:0", config).await.unwrap();
+    let addr = server.local_addr();
+    server.run();
+
+    let first = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let second = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    first.send_to(&datagram(0, 1, b"one"), addr).await.unwrap();
+    let (uuid, packet) = server.receive().await.unwrap();
+    assert_eq!(packet.payload, &b"one"[..]);
+
+    second.send_to(&datagram(0, 1, b"two"), addr).await.unwrap();
+    let ignored = tokio::time::timeout(Duration::from_millis(100), server.receive()).await;
+    assert!(ignored.is_err());
+    assert!(server.connection_info(uuid).is_some());
+}
+
+#[tokio::test]
+async fn idle_peers_are_reported_closed() {
+    let config = UdpConfig {
+        retransmit_interval: Duration::from_millis(20),
+        idle_timeout: Some(Duration::from_millis(100)),
+        ..UdpConfig::default()
+    };
+    let mut server = UdpServerTransport::new("127.0.0.1:0", config).await.unwrap();
+    let addr = server.local_addr();
+    server.run();
+    let mut closed = server.closed_connections().unwrap();
+
+    let peer = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    peer.send_to(&datagram(0, 1, b"hi"), addr).await.unwrap();
+    let (uuid, _) = server.receive().await.unwrap();
+    let expired = tokio::time::timeout(Duration::from_secs(2), closed.recv()).await.unwrap();
+    assert_eq!(expired, Some(uuid));
+    assert!(server.connection_info(uuid).is_none());
+}
+
+#[tokio::test]
+async fn overflowed_reliable_datagram_is_not_acked_until_delivered() {
+    let mut server = UdpServerTransport::new("127.0.0.1:0", reliable()).await.unwrap();
+    let addr = server.local_addr();
+    server.run();
+
+    // 上层不读取，把接收队列填满
+    let peer = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let mut buf = [0u8; 256];
+    let mut sequence = 0;
+    loop {
+        sequence += 1;
+        peer.send_to(&datagram(1, sequence, b"fill"), addr).await.unwrap();
+        let ack = tokio::time::timeout(Duration::from_millis(100), peer.recv_from(&mut buf)).await;
+        if ack.is_err() {
+            break;
+        }
+        assert!(sequence <= 1000, "receive queue never filled");
+    }
+
+    // 队列腾出空间后，重传的数据报被交付并确认
+    for _ in 1..sequence {
+        server.receive().await.unwrap();
+    }
+    peer.send_to(&datagram(1, sequence, b"last"), addr).await.unwrap();
+    let (len, _) = peer.recv_from(&mut buf).await.unwrap();
+    assert_eq!(buf[..len], prefix(2, sequence)[..]);
+    let (_, packet) = server.receive().await.unwrap();
+    assert_eq!(packet.payload, &b"last"[..]);
+}
@@ -0,0 +1,42 @@
+#![cfg(unix)]
+
+use bytes::Bytes;
+use rummy::protocol::{Packet, PacketHeader};
+use rummy::rpc::{RpcClient, RpcServer};
+use rummy::transport::{Transport, UnixClientTransport, UnixServerTransport};
+
+#[tokio::test]
+async fn rpc_over_filesystem_socket() {
+    let path = std::env::temp_dir().join(format!("rummy-{}.sock", uuid::Uuid::new_v4()));
+    let mut transport = UnixServerTransport::new(&path).await.unwrap();
+    transport.run();
+    tokio::spawn(RpcServer::with_handler(transport, |_, payload: Bytes| async move {
+        Ok(payload.to_ascii_uppercase())
+    }).serve());
+
+    let client = RpcClient::new(UnixClientTransport::connect(&path).await.unwrap());
+    assert_eq!(client.call(b"ping".to_vec()).await.unwrap(), &b"PING"[..]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn abstract_socket_reports_peer_credentials() {
+    let path = format!("@rummy-{}", uuid::Uuid::new_v4());
+    let mut server = UnixServerTransport::new(&path).await.unwrap();
+    server.run();
+
+    let client = UnixClientTransport::connect(&path).await.unwrap();
+    let header = PacketHeader::from_payload(b"hello", 0);
+    client.send(uuid::Uuid::nil(), Packet::new(header, &b"hello"[..])).await.unwrap();
+
+    let (uuid, packet) = server.receive().await.unwrap();
+    assert_eq!(packet.payload, &b"hello"[..]);
+    let info = server.connection_info(uuid).await.unwrap();
+    assert_eq!(info.uuid, uuid);
+    assert_eq!(info.peer_addr, None);
+    // 客户端就是本进程
+    let cred = info.peer_cred.unwrap();
+    assert_eq!(cred.pid, Some(std::process::id() as i32));
+    // 抽象命名空间不在文件系统中创建文件
+    assert!(!std::path::Path::new(&path).exists());
+}
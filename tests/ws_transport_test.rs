@@ -0,0 +1,51 @@
+use bytes::Bytes;
+use rummy::rpc::{CallContext, RpcClient, RpcServer};
+use rummy::transport::{TcpClientTransport, TcpServerTransport, WsClientTransport, WsServerTransport};
+
+async fn echo(_: CallContext, payload: Bytes) -> rummy::rpc::HandlerResult {
+    Ok(payload.to_ascii_uppercase())
+}
+
+#[tokio::test]
+async fn rpc_over_websocket() {
+    let mut transport = WsServerTransport::new("127.0.0.1:0", "/rummy").await.unwrap();
+    let addr = transport.local_addr();
+    transport.run();
+    tokio::spawn(RpcServer::with_handler(transport, echo).serve());
+
+    let client = RpcClient::new(WsClientTransport::connect(&format!("ws://{}/rummy", addr)).await.unwrap());
+    assert_eq!(client.call(b"ws".to_vec()).await.unwrap(), &b"WS"[..]);
+
+    // 路径不匹配时拒绝升级
+    assert!(WsClientTransport::connect(&format!("ws://{}/other", addr)).await.is_err());
+}
+
+#[tokio::test]
+async fn tcp_and_websocket_share_a_port() {
+    let mut transport = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    transport.set_websocket("/rummy");
+    let addr = transport.local_addr();
+    transport.run();
+    tokio::spawn(RpcServer::with_handler(transport, echo).serve());
+
+    let tcp = RpcClient::new(TcpClientTransport::connect(addr).await.unwrap());
+    let ws = RpcClient::new(WsClientTransport::connect(&format!("ws://{}/rummy", addr)).await.unwrap());
+    assert_eq!(tcp.call(b"tcp".to_vec()).await.unwrap(), &b"TCP"[..]);
+    assert_eq!(ws.call(b"ws".to_vec()).await.unwrap(), &b"WS"[..]);
+}
+
+#[tokio::test]
+async fn silent_connection_on_shared_port_is_closed() {
+    use tokio::io::AsyncReadExt;
+
+    let mut transport = TcpServerTransport::new("127.0.0.1:0").await.unwrap();
+    transport.set_websocket("/rummy");
+    let addr = transport.local_addr();
+    transport.run();
+
+    // 连上后不发任何数据，服务端在握手超时后断开
+    let mut silent = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let mut buf = [0u8; 1];
+    let read = tokio::time::timeout(std::time::Duration::from_secs(15), silent.read(&mut buf)).await;
+    assert!(matches!(read, Ok(Ok(0)) | Ok(Err(_))));
+}